@@ -94,6 +94,10 @@ impl<'de> serde::de::Visitor<'de> for Visitor {
             "$usermarshal" => map.next_value::<DeserializeUsertype>()?.into_umarshal(),
             "$cdata" => map.next_value::<DeserializeUsertype>()?.into_cdata(),
             "$string" => Value::String(map.next_value::<DeserializeBytes>()?.0.into()),
+            "$id" => map.next_value::<DeserializeLinked>()?.into(),
+            "$ref" => crate::links::ref_object(map.next_value()?),
+            "$bignum" => crate::links::bignum_object(map.next_value()?),
+            "$hash_default" => map.next_value::<DeserializeHashDefault>()?.into(),
             _ => return Err(A::Error::custom("invalid data type")),
         };
 
@@ -404,3 +408,29 @@ impl DeserializeUsertype {
         }
     }
 }
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "Linked")]
+struct DeserializeLinked {
+    id: i32,
+    value: DeserializeValue,
+}
+
+impl From<DeserializeLinked> for alox_48::Value {
+    fn from(val: DeserializeLinked) -> Self {
+        crate::links::id_object(val.id, val.value.0)
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "HashDefault")]
+struct DeserializeHashDefault {
+    hash: DeserializeHash,
+    default: DeserializeValue,
+}
+
+impl From<DeserializeHashDefault> for alox_48::Value {
+    fn from(val: DeserializeHashDefault) -> Self {
+        crate::links::hash_default_object(val.hash.0, val.default.0)
+    }
+}
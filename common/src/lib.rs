@@ -6,7 +6,11 @@
 )]
 
 mod de;
+pub mod links;
 mod ser;
+mod trace;
+
+pub use trace::{Trace, marshal_path_at};
 
 pub struct Value(pub alox_48::Value);
 
@@ -83,7 +87,6 @@ impl Format {
     }
 }
 
-// TODO provide trace
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
 pub enum ConvError {
@@ -107,6 +110,46 @@ pub enum ConvError {
     Yaml(#[from] serde_yaml_ng::Error),
     #[error("{0}")]
     Io(#[from] std::io::Error),
+    /// An error with where in the data it happened.
+    #[error("{source} (at {trace})")]
+    Traced {
+        source: Box<ConvError>,
+        trace: Trace,
+    },
+}
+
+impl ConvError {
+    fn traced(source: impl Into<ConvError>, trace: Trace) -> Self {
+        let source = source.into();
+        if trace.is_empty() {
+            return source;
+        }
+        Self::Traced {
+            source: Box::new(source),
+            trace,
+        }
+    }
+
+    /// Where in the data this error happened, if known.
+    pub fn trace(&self) -> Option<&Trace> {
+        match self {
+            Self::Traced { trace, .. } => Some(trace),
+            _ => None,
+        }
+    }
+}
+
+fn text_error<E>(error: serde_path_to_error::Error<E>) -> ConvError
+where
+    E: Into<ConvError>,
+{
+    let path = if error.path().iter().next().is_some() {
+        error.path().to_string()
+    } else {
+        String::new()
+    };
+    let trace = Trace { path, offset: None };
+    ConvError::traced(error.into_inner(), trace)
 }
 
 // FIXME shitty hack because serde_yaml doesn't support binary and we have no way to recover from serialization errors
@@ -122,6 +165,50 @@ fn binary_bytes_allowed() -> bool {
     BYTES_ALLOWED.get()
 }
 
+/// Reads one marshal value from the start of `data`, and how many bytes it took up.
+///
+/// `start` is where `data` starts in the whole input, for error offsets.
+fn read_marshal<T>(data: &[u8], start: usize) -> Result<(T, usize), ConvError>
+where
+    T: for<'de> alox_48::Deserialize<'de>,
+{
+    let mut deserializer = alox_48::Deserializer::new(data)?;
+    match alox_48::path_to_error::deserialize(&mut deserializer) {
+        Ok(value) => Ok((value, deserializer.current_position())),
+        Err((error, trace)) => {
+            // only needed to name hash keys in the path
+            let root = alox_48::from_bytes::<alox_48::Value>(data).ok();
+            let trace = Trace {
+                path: trace::marshal_path(&trace, root.as_ref(), trace::Direction::Reading),
+                offset: Some(start + deserializer.current_position()),
+            };
+            Err(ConvError::traced(error, trace))
+        }
+    }
+}
+
+/// Adds which value of a multi-value input `error` happened in to its trace.
+fn in_value(error: ConvError, index: usize) -> ConvError {
+    match error {
+        ConvError::Traced { source, mut trace } => {
+            let separator = if trace.path.is_empty() || trace.path.starts_with('[') {
+                ""
+            } else {
+                "."
+            };
+            trace.path = format!("[{index}]{separator}{}", trace.path);
+            ConvError::Traced { source, trace }
+        }
+        error => {
+            let trace = Trace {
+                path: format!("[{index}]"),
+                offset: None,
+            };
+            ConvError::traced(error, trace)
+        }
+    }
+}
+
 pub fn conv_read<R, T>(from: Format, mut input: R) -> Result<T, ConvError>
 where
     R: std::io::Read,
@@ -133,14 +220,33 @@ where
         Format::Marshal => {
             let mut data = vec![];
             input.read_to_end(&mut data)?;
-            alox_48::from_bytes::<T>(&data)?
+            read_marshal(&data, 0)?.0
+        }
+        Format::Json => {
+            let mut deserializer = serde_json::Deserializer::from_reader(input);
+            let value = serde_path_to_error::deserialize(&mut deserializer).map_err(text_error)?;
+            deserializer.end()?;
+            value
+        }
+        Format::Ron => {
+            let mut data = vec![];
+            input.read_to_end(&mut data)?;
+            let options = ron::Options::default();
+            let mut deserializer = ron::Deserializer::from_bytes_with_options(&data, &options)?;
+            let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|error| {
+                let path = error.path().clone();
+                // ron only knows the line and column while it's still deserializing
+                let error = deserializer.span_error(error.into_inner());
+                text_error(serde_path_to_error::Error::new(path, error))
+            })?;
+            deserializer.end().map_err(|e| deserializer.span_error(e))?;
+            value
         }
-        Format::Json => serde_json::from_reader(input)?,
-        Format::Ron => ron::Options::default().from_reader(input)?,
         // Format::Saphyr => {
         //     let mut iter = serde_saphyr::read(&mut input);
         //     iter.next().ok_or(ConvError::SaphyrNoDocument)??
         // }
+        // serde_yaml already includes the path in its errors
         Format::Yaml => serde_yaml_ng::from_reader(input)?,
     };
     Ok(value)
@@ -155,25 +261,198 @@ where
 
     match to {
         Format::Marshal => {
-            let data = alox_48::to_bytes(value)?;
-            output.write_all(&data)?;
+            let mut serializer = alox_48::Serializer::new();
+            if let Err((error, trace)) = alox_48::path_to_error::serialize(value, &mut serializer) {
+                let trace = Trace {
+                    path: trace::marshal_path(&trace, None, trace::Direction::Writing),
+                    offset: None,
+                };
+                return Err(ConvError::traced(error, trace));
+            }
+            output.write_all(&serializer.output)?;
         }
         Format::Json => {
             let mut ser = serde_json::Serializer::pretty(output);
-            serde::Serialize::serialize(&value, &mut ser)?;
+            serde_path_to_error::serialize(&value, &mut ser).map_err(text_error)?;
         }
         Format::Ron => {
             let config = ron::ser::PrettyConfig::default();
-            ron::Options::default().to_io_writer_pretty(output, &value, config)?;
+            let mut text = String::new();
+            let mut ser =
+                ron::Serializer::with_options(&mut text, Some(config), &ron::Options::default())?;
+            serde_path_to_error::serialize(&value, &mut ser).map_err(text_error)?;
+            output.write_all(text.as_bytes())?;
         }
         // Format::Saphyr => {
         //     serde_saphyr::to_io_writer(&mut output, &value)?;
         // }
         Format::Yaml => {
             let mut ser = serde_yaml_ng::Serializer::new(output);
-            serde::Serialize::serialize(&value, &mut ser)?;
+            serde_path_to_error::serialize(&value, &mut ser).map_err(text_error)?;
         }
     }
 
     Ok(())
 }
+
+/// Like [`conv_read`], but object links in marshal data are preserved as `$id`/`$ref` pairs.
+pub fn conv_read_linked<R>(from: Format, mut input: R) -> Result<Value, ConvError>
+where
+    R: std::io::Read,
+{
+    if from != Format::Marshal {
+        return conv_read(from, input);
+    }
+
+    set_binary_bytes_allowed(true);
+    let mut data = vec![];
+    input.read_to_end(&mut data)?;
+    let value = links::from_bytes(&data)?;
+    Ok(Value(value))
+}
+
+/// Like [`conv_write`], but `$id`/`$ref` pairs are written to marshal as object links.
+///
+/// `floats` is how floats are written to marshal, see [`links::FloatStyle::guess`].
+pub fn conv_write_linked<W>(
+    value: &Value,
+    to: Format,
+    floats: links::FloatStyle,
+    mut output: W,
+) -> Result<(), ConvError>
+where
+    W: std::io::Write,
+{
+    if to != Format::Marshal {
+        return conv_write(value, to, output);
+    }
+
+    set_binary_bytes_allowed(true);
+    let data = links::to_bytes(&value.0, floats)?;
+    output.write_all(&data)?;
+    Ok(())
+}
+
+/// Reads consecutive marshal dumps until the end of `data`, with `read` returning each value and
+/// how many bytes it took up.
+fn read_dumps<T>(
+    data: &[u8],
+    mut read: impl FnMut(&[u8], usize) -> Result<(T, usize), ConvError>,
+) -> Result<Vec<T>, ConvError> {
+    let mut values = vec![];
+    let mut position = 0;
+    while position < data.len() {
+        let (value, len) =
+            read(&data[position..], position).map_err(|e| in_value(e, values.len()))?;
+        values.push(value);
+        position += len;
+    }
+    Ok(values)
+}
+
+/// Like [`conv_read`], but reads every value in the input.
+///
+/// Marshal data is read as consecutive dumps until the end of the input (like a file written with
+/// several `Marshal.dump` calls), YAML as one document per value, and other text formats as a
+/// top-level sequence.
+pub fn conv_read_multi<R, T>(from: Format, mut input: R) -> Result<Vec<T>, ConvError>
+where
+    R: std::io::Read,
+    T: for<'de> serde::Deserialize<'de> + for<'de> alox_48::Deserialize<'de>,
+{
+    set_binary_bytes_allowed(from != Format::Yaml);
+
+    match from {
+        Format::Marshal => {
+            let mut data = vec![];
+            input.read_to_end(&mut data)?;
+            read_dumps(&data, read_marshal)
+        }
+        Format::Yaml => {
+            let mut data = vec![];
+            input.read_to_end(&mut data)?;
+            // serde_yaml reads an empty stream as one empty document, but that is how no values are written
+            if data.iter().all(u8::is_ascii_whitespace) {
+                return Ok(vec![]);
+            }
+            serde_yaml_ng::Deserializer::from_slice(&data)
+                .enumerate()
+                .map(|(index, document)| {
+                    <T as serde::Deserialize>::deserialize(document)
+                        .map_err(|e| in_value(e.into(), index))
+                })
+                .collect()
+        }
+        _ => conv_read(from, input),
+    }
+}
+
+/// Like [`conv_write`], but writes several values.
+///
+/// Marshal data is written as consecutive dumps, YAML as one document per value, and other text
+/// formats as a top-level sequence.
+pub fn conv_write_multi<W, T>(values: &[T], to: Format, mut output: W) -> Result<(), ConvError>
+where
+    W: std::io::Write,
+    T: serde::Serialize + alox_48::Serialize,
+{
+    set_binary_bytes_allowed(to != Format::Yaml);
+
+    match to {
+        Format::Marshal => {
+            for (index, value) in values.iter().enumerate() {
+                conv_write(value, to, &mut output).map_err(|e| in_value(e, index))?;
+            }
+        }
+        Format::Yaml => {
+            // every value serialized with the same serializer is a new document
+            let mut ser = serde_yaml_ng::Serializer::new(output);
+            for (index, value) in values.iter().enumerate() {
+                serde_path_to_error::serialize(value, &mut ser)
+                    .map_err(|e| in_value(text_error(e), index))?;
+            }
+        }
+        _ => conv_write(values, to, output)?,
+    }
+    Ok(())
+}
+
+/// Like [`conv_read_multi`], but object links in marshal data are preserved as `$id`/`$ref` pairs.
+pub fn conv_read_linked_multi<R>(from: Format, mut input: R) -> Result<Vec<Value>, ConvError>
+where
+    R: std::io::Read,
+{
+    if from != Format::Marshal {
+        return conv_read_multi(from, input);
+    }
+
+    set_binary_bytes_allowed(true);
+    let mut data = vec![];
+    input.read_to_end(&mut data)?;
+    read_dumps(&data, |data, _| {
+        let (value, len) = links::from_bytes_prefix(data)?;
+        Ok((Value(value), len))
+    })
+}
+
+/// Like [`conv_write_multi`], but `$id`/`$ref` pairs are written to marshal as object links.
+pub fn conv_write_linked_multi<W>(
+    values: &[Value],
+    to: Format,
+    floats: links::FloatStyle,
+    mut output: W,
+) -> Result<(), ConvError>
+where
+    W: std::io::Write,
+{
+    if to != Format::Marshal {
+        return conv_write_multi(values, to, output);
+    }
+
+    set_binary_bytes_allowed(true);
+    for (index, value) in values.iter().enumerate() {
+        let data = links::to_bytes(&value.0, floats).map_err(|e| in_value(e.into(), index))?;
+        output.write_all(&data)?;
+    }
+    Ok(())
+}
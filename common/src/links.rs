@@ -0,0 +1,1127 @@
+//! Marshal reading and writing that preserves object links.
+//!
+//! alox-48 turns object links into copies when reading and never writes them,
+//! so shared objects end up duplicated after a round trip.
+//!
+//! Here shared objects are represented with two reserved classes instead:
+//! the first occurrence of a shared object is wrapped in a `$id`, and every link to it is replaced by a `$ref` with the same id.
+//! Neither is a valid Ruby class name, so they can't clash with real data.
+//!
+//! alox-48 also can't represent bignums or hashes with a default value, which get the reserved
+//! classes `$bignum` and `$hash_default`.
+#![allow(
+    clippy::cast_possible_wrap,
+    clippy::cast_sign_loss,
+    clippy::too_many_lines
+)]
+
+use alox_48::{DeError, SerError, Value};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::path::Path;
+
+const ID_CLASS: &str = "$id";
+const REF_CLASS: &str = "$ref";
+const BIGNUM_CLASS: &str = "$bignum";
+const HASH_DEFAULT_CLASS: &str = "$hash_default";
+
+// Marshal writes integers outside of this range as bignums.
+const FIXNUM_MIN: i32 = -(1 << 30);
+const FIXNUM_MAX: i32 = (1 << 30) - 1;
+
+pub(crate) fn id_object(id: i32, value: Value) -> Value {
+    let mut fields = alox_48::RbFields::new();
+    fields.insert("id".into(), Value::Integer(id));
+    fields.insert("value".into(), value);
+    Value::Object(alox_48::Object {
+        class: ID_CLASS.into(),
+        fields,
+    })
+}
+
+pub(crate) fn ref_object(id: i32) -> Value {
+    let mut fields = alox_48::RbFields::new();
+    fields.insert("id".into(), Value::Integer(id));
+    Value::Object(alox_48::Object {
+        class: REF_CLASS.into(),
+        fields,
+    })
+}
+
+/// A bignum, as decimal digits with an optional `-`.
+pub(crate) fn bignum_object(digits: String) -> Value {
+    let mut fields = alox_48::RbFields::new();
+    fields.insert("value".into(), Value::String(digits.into()));
+    Value::Object(alox_48::Object {
+        class: BIGNUM_CLASS.into(),
+        fields,
+    })
+}
+
+pub(crate) fn hash_default_object(hash: alox_48::RbHash, default: Value) -> Value {
+    let mut fields = alox_48::RbFields::new();
+    fields.insert("hash".into(), Value::Hash(hash));
+    fields.insert("default".into(), default);
+    Value::Object(alox_48::Object {
+        class: HASH_DEFAULT_CLASS.into(),
+        fields,
+    })
+}
+
+/// Whether this object is a well-formed `$id`, `$ref`, `$bignum`, or `$hash_default`.
+pub(crate) fn is_reserved(object: &alox_48::Object) -> bool {
+    as_id(object).is_some()
+        || as_ref(object).is_some()
+        || as_bignum(object).is_some()
+        || as_hash_default(object).is_some()
+}
+
+/// Returns the id and value of a `$id` object.
+pub(crate) fn as_id(object: &alox_48::Object) -> Option<(i32, &Value)> {
+    if object.class.as_str() != ID_CLASS {
+        return None;
+    }
+    let id = object.fields.get("id").and_then(Value::as_integer)?;
+    let value = object.fields.get("value")?;
+    Some((*id, value))
+}
+
+/// Returns the id of a `$ref` object.
+pub(crate) fn as_ref(object: &alox_48::Object) -> Option<i32> {
+    if object.class.as_str() != REF_CLASS {
+        return None;
+    }
+    object.fields.get("id").and_then(Value::as_integer).copied()
+}
+
+/// Returns the digits of a `$bignum` object.
+pub(crate) fn as_bignum(object: &alox_48::Object) -> Option<&str> {
+    if object.class.as_str() != BIGNUM_CLASS {
+        return None;
+    }
+    let digits = object.fields.get("value")?.as_string()?;
+    std::str::from_utf8(&digits.data).ok()
+}
+
+/// Returns the hash and default value of a `$hash_default` object.
+pub(crate) fn as_hash_default(object: &alox_48::Object) -> Option<(&alox_48::RbHash, &Value)> {
+    if object.class.as_str() != HASH_DEFAULT_CLASS {
+        return None;
+    }
+    let hash = object.fields.get("hash")?.as_hash()?;
+    let default = object.fields.get("default")?;
+    Some((hash, default))
+}
+
+/// Deserialize marshal data, representing object links with `$id` and `$ref`.
+pub fn from_bytes(data: &[u8]) -> Result<Value, DeError> {
+    from_bytes_prefix(data).map(|(value, _)| value)
+}
+
+/// Like [`from_bytes`], but `data` can continue after the value. Also returns how many bytes the
+/// value took up.
+pub(crate) fn from_bytes_prefix(data: &[u8]) -> Result<(Value, usize), DeError> {
+    // We only know which objects are linked to after reading everything,
+    // so the first pass figures that out and the second pass wraps them.
+    let mut reader = Reader::new(data, None)?;
+    reader.read_value()?;
+
+    let linked = reader.linked;
+    let mut reader = Reader::new(data, Some(&linked))?;
+    let value = reader.read_value()?;
+    Ok((value, reader.position))
+}
+
+/// How floats are written, which depends on the Ruby version the game uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FloatStyle {
+    /// Ruby 1.9 and later (VX Ace): the shortest digits that round trip.
+    #[default]
+    Shortest,
+    /// Ruby 1.8 (XP and VX): 17 significant digits, then a nul and the low bits of the mantissa.
+    Ruby18,
+}
+
+impl FloatStyle {
+    /// The style of the game that uses files with `path`'s extension.
+    pub fn guess(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("rxdata" | "rvdata") => Self::Ruby18,
+            _ => Self::Shortest,
+        }
+    }
+}
+
+/// Serialize a value to marshal, writing `$ref`s as object links.
+pub fn to_bytes(value: &Value, floats: FloatStyle) -> Result<Vec<u8>, SerError> {
+    let mut writer = Writer {
+        output: vec![4, 8],
+        symbols: HashMap::new(),
+        object_count: 0,
+        ids: HashMap::new(),
+        floats,
+    };
+    writer.write_value(value, None)?;
+    Ok(writer.output)
+}
+
+struct Reader<'de, 'a> {
+    input: &'de [u8],
+    position: usize,
+    symbols: Vec<alox_48::Symbol>,
+    object_count: usize,
+    // object indices that something links to
+    linked: HashSet<usize>,
+    // which objects to wrap in a `$id`, if known yet
+    wrap: Option<&'a HashSet<usize>>,
+}
+
+impl<'de, 'a> Reader<'de, 'a> {
+    fn new(input: &'de [u8], wrap: Option<&'a HashSet<usize>>) -> Result<Self, DeError> {
+        match input {
+            [4, 8, ..] => Ok(Self {
+                input,
+                position: 2,
+                symbols: vec![],
+                object_count: 0,
+                linked: HashSet::new(),
+                wrap,
+            }),
+            [major, minor, ..] => Err(DeError::custom(format!(
+                "unsupported marshal version {major}.{minor}"
+            ))),
+            _ => Err(DeError::custom("unexpected end of input")),
+        }
+    }
+
+    fn peek_byte(&self) -> Result<u8, DeError> {
+        self.input
+            .get(self.position)
+            .copied()
+            .ok_or_else(|| DeError::custom("unexpected end of input"))
+    }
+
+    fn next_byte(&mut self) -> Result<u8, DeError> {
+        let byte = self.peek_byte()?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn next_bytes(&mut self, len: usize) -> Result<&'de [u8], DeError> {
+        let bytes = self
+            .input
+            .get(self.position..self.position + len)
+            .ok_or_else(|| DeError::custom("unexpected end of input"))?;
+        self.position += len;
+        Ok(bytes)
+    }
+
+    fn read_int(&mut self) -> Result<i32, DeError> {
+        let c = self.next_byte()? as i8;
+        let value = match c {
+            0 => 0,
+            5..=127 => i64::from(c) - 5,
+            -128..=-5 => i64::from(c) + 5,
+            1..=4 => {
+                let mut value = 0;
+                for i in 0..c {
+                    value |= i64::from(self.next_byte()?) << (8 * i);
+                }
+                value
+            }
+            -4..=-1 => {
+                let mut value = -1;
+                for i in 0..-c {
+                    value &= !(0xFF << (8 * i));
+                    value |= i64::from(self.next_byte()?) << (8 * i);
+                }
+                value
+            }
+        };
+        i32::try_from(value).map_err(DeError::custom)
+    }
+
+    fn read_len(&mut self) -> Result<usize, DeError> {
+        let len = self.read_int()?;
+        usize::try_from(len).map_err(|_| DeError::custom(format!("invalid length {len}")))
+    }
+
+    fn read_bytes(&mut self) -> Result<&'de [u8], DeError> {
+        let len = self.read_len()?;
+        self.next_bytes(len)
+    }
+
+    fn read_symbol(&mut self) -> Result<alox_48::Symbol, DeError> {
+        match self.next_byte()? {
+            b':' => {
+                let bytes = self.read_bytes()?;
+                let symbol = alox_48::Symbol::from(
+                    String::from_utf8(bytes.to_vec()).map_err(DeError::custom)?,
+                );
+                self.symbols.push(symbol.clone());
+                Ok(symbol)
+            }
+            b';' => {
+                let index = self.read_len()?;
+                self.symbols
+                    .get(index)
+                    .cloned()
+                    .ok_or_else(|| DeError::custom(format!("invalid symbol link {index}")))
+            }
+            tag => Err(DeError::custom(format!(
+                "expected a symbol, got tag {:?}",
+                char::from(tag)
+            ))),
+        }
+    }
+
+    fn read_fields(&mut self) -> Result<alox_48::RbFields, DeError> {
+        let len = self.read_len()?;
+        let mut fields = alox_48::RbFields::with_capacity(len);
+        for _ in 0..len {
+            let key = self.read_symbol()?;
+            let value = self.read_value()?;
+            fields.insert(key, value);
+        }
+        Ok(fields)
+    }
+
+    fn register(&mut self) -> usize {
+        let index = self.object_count;
+        self.object_count += 1;
+        index
+    }
+
+    fn read_value(&mut self) -> Result<Value, DeError> {
+        let (value, index) = self.read_entry()?;
+        let value = match index {
+            Some(index) if self.wrap.is_some_and(|wrap| wrap.contains(&index)) => {
+                id_object(index as i32, value)
+            }
+            _ => value,
+        };
+        Ok(value)
+    }
+
+    // Returns the value and its index in the object table, if it has one.
+    fn read_entry(&mut self) -> Result<(Value, Option<usize>), DeError> {
+        let entry = match self.peek_byte()? {
+            b':' | b';' => (Value::Symbol(self.read_symbol()?), None),
+            _ => match self.next_byte()? {
+                b'0' => (Value::Nil, None),
+                b'T' => (Value::Bool(true), None),
+                b'F' => (Value::Bool(false), None),
+                b'i' => (Value::Integer(self.read_int()?), None),
+                b'@' => {
+                    let index = self.read_len()?;
+                    if index >= self.object_count {
+                        return Err(DeError::custom(format!("invalid object link {index}")));
+                    }
+                    self.linked.insert(index);
+                    (ref_object(index as i32), None)
+                }
+                b'f' => {
+                    let index = self.register();
+                    let float = parse_float(self.read_bytes()?)?;
+                    (Value::Float(float), Some(index))
+                }
+                b'l' => {
+                    let index = self.register();
+                    let negative = match self.next_byte()? {
+                        b'+' => false,
+                        b'-' => true,
+                        sign => {
+                            return Err(DeError::custom(format!(
+                                "invalid bignum sign {:?}",
+                                char::from(sign)
+                            )));
+                        }
+                    };
+                    // the length is in 16 bit words
+                    let len = self.read_len()?;
+                    let bytes = self.next_bytes(len * 2)?;
+                    (bignum_value(negative, bytes), Some(index))
+                }
+                b'"' => {
+                    let index = self.register();
+                    let data = self.read_bytes()?.to_vec();
+                    (Value::String(alox_48::RbString { data }), Some(index))
+                }
+                b'/' => {
+                    let index = self.register();
+                    let data = self.read_bytes()?.to_vec().into();
+                    let flags = self.next_byte()?;
+                    (Value::Regex { data, flags }, Some(index))
+                }
+                b'c' | b'm' => {
+                    let tag = self.input[self.position - 1];
+                    let index = self.register();
+                    let name = String::from_utf8(self.read_bytes()?.to_vec())
+                        .map_err(DeError::custom)?
+                        .into();
+                    let value = if tag == b'c' {
+                        Value::Class(name)
+                    } else {
+                        Value::Module(name)
+                    };
+                    (value, Some(index))
+                }
+                b'[' => {
+                    let index = self.register();
+                    let len = self.read_len()?;
+                    let array = (0..len)
+                        .map(|_| self.read_value())
+                        .collect::<Result<_, _>>()?;
+                    (Value::Array(array), Some(index))
+                }
+                b'{' => {
+                    let index = self.register();
+                    let len = self.read_len()?;
+                    let mut hash = alox_48::RbHash::with_capacity(len);
+                    for _ in 0..len {
+                        let key = self.read_value()?;
+                        let value = self.read_value()?;
+                        hash.insert(key, value);
+                    }
+                    (Value::Hash(hash), Some(index))
+                }
+                b'}' => {
+                    let index = self.register();
+                    let len = self.read_len()?;
+                    let mut hash = alox_48::RbHash::with_capacity(len);
+                    for _ in 0..len {
+                        let key = self.read_value()?;
+                        let value = self.read_value()?;
+                        hash.insert(key, value);
+                    }
+                    let default = self.read_value()?;
+                    (hash_default_object(hash, default), Some(index))
+                }
+                b'o' => {
+                    let class = self.read_symbol()?;
+                    let index = self.register();
+                    let fields = self.read_fields()?;
+                    (
+                        Value::Object(alox_48::Object { class, fields }),
+                        Some(index),
+                    )
+                }
+                b'S' => {
+                    let class = self.read_symbol()?;
+                    let index = self.register();
+                    let fields = self.read_fields()?;
+                    (
+                        Value::RbStruct(alox_48::RbStruct { class, fields }),
+                        Some(index),
+                    )
+                }
+                b'u' => {
+                    let class = self.read_symbol()?;
+                    let data = self.read_bytes()?.to_vec();
+                    // Ruby registers userdata after loading it
+                    let index = self.register();
+                    (
+                        Value::Userdata(alox_48::Userdata { class, data }),
+                        Some(index),
+                    )
+                }
+                b'U' => {
+                    let class = self.read_symbol()?;
+                    let index = self.register();
+                    let value = Box::new(self.read_value()?);
+                    (Value::UserMarshal { class, value }, Some(index))
+                }
+                b'd' => {
+                    let class = self.read_symbol()?;
+                    let index = self.register();
+                    let value = Box::new(self.read_value()?);
+                    (Value::Data { class, value }, Some(index))
+                }
+                // these modify the object that comes after them, so they share its index
+                b'e' => {
+                    let module = self.read_symbol()?;
+                    let (value, index) = self.read_entry()?;
+                    let value = Box::new(value);
+                    (Value::Extended { module, value }, index)
+                }
+                b'C' => {
+                    let class = self.read_symbol()?;
+                    let (value, index) = self.read_entry()?;
+                    let value = Box::new(value);
+                    (Value::UserClass { class, value }, index)
+                }
+                b'I' if self.peek_byte()? == b'u' => {
+                    // userdata with instance variables is only registered once those are read
+                    self.position += 1;
+                    let class = self.read_symbol()?;
+                    let data = self.read_bytes()?.to_vec();
+                    let fields = self.read_fields()?;
+                    let index = self.register();
+                    let value = Box::new(Value::Userdata(alox_48::Userdata { class, data }));
+                    (
+                        Value::Instance(alox_48::Instance { value, fields }),
+                        Some(index),
+                    )
+                }
+                b'I' => {
+                    let (value, index) = self.read_entry()?;
+                    let value = Box::new(value);
+                    let fields = self.read_fields()?;
+                    (Value::Instance(alox_48::Instance { value, fields }), index)
+                }
+                tag => {
+                    return Err(DeError::custom(format!(
+                        "unsupported tag {:?} at offset {}",
+                        char::from(tag),
+                        self.position - 1
+                    )));
+                }
+            },
+        };
+        Ok(entry)
+    }
+}
+
+/// An integer if the bignum fits in one, otherwise a `$bignum`.
+///
+/// `bytes` is the magnitude, little endian.
+fn bignum_value(negative: bool, bytes: &[u8]) -> Value {
+    let significant = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    if significant <= 4 {
+        let mut magnitude = [0; 8];
+        magnitude[..significant].copy_from_slice(&bytes[..significant]);
+        let magnitude = i64::from_le_bytes(magnitude);
+        let value = if negative { -magnitude } else { magnitude };
+        if let Ok(value) = i32::try_from(value) {
+            return Value::Integer(value);
+        }
+    }
+
+    // repeatedly divide the big endian magnitude by 10 to get the digits, least significant first
+    let mut magnitude: Vec<u8> = bytes[..significant].iter().rev().copied().collect();
+    let mut digits = vec![];
+    while !magnitude.is_empty() {
+        let mut remainder = 0u32;
+        for byte in &mut magnitude {
+            let current = remainder << 8 | u32::from(*byte);
+            *byte = (current / 10) as u8;
+            remainder = current % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+        let leading = magnitude.iter().take_while(|&&b| b == 0).count();
+        magnitude.drain(..leading);
+    }
+    if negative {
+        digits.push(b'-');
+    }
+    digits.reverse();
+    bignum_object(String::from_utf8(digits).expect("digits should be ascii"))
+}
+
+/// The sign and little endian magnitude of a `$bignum`'s digits.
+fn parse_bignum(digits: &str) -> Option<(bool, Vec<u8>)> {
+    let (negative, digits) = match digits.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, digits),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut magnitude = vec![];
+    for digit in digits.bytes() {
+        let mut carry = u32::from(digit - b'0');
+        for byte in &mut magnitude {
+            let current = u32::from(*byte) * 10 + carry;
+            *byte = current as u8;
+            carry = current >> 8;
+        }
+        if carry > 0 {
+            magnitude.push(carry as u8);
+        }
+    }
+    Some((negative, magnitude))
+}
+
+fn parse_float(bytes: &[u8]) -> Result<f64, DeError> {
+    // Ruby 1.8 appends mantissa bytes after a nul, which are redundant for our purposes
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    let string = std::str::from_utf8(&bytes[..end]).map_err(DeError::custom)?;
+    match string {
+        "inf" => Ok(f64::INFINITY),
+        "-inf" => Ok(f64::NEG_INFINITY),
+        "nan" => Ok(f64::NAN),
+        _ => string.parse().map_err(DeError::custom),
+    }
+}
+
+struct Writer {
+    output: Vec<u8>,
+    symbols: HashMap<alox_48::Symbol, usize>,
+    object_count: usize,
+    // `$id`s to their index in the object table
+    ids: HashMap<i32, usize>,
+    floats: FloatStyle,
+}
+
+impl Writer {
+    fn write_int(&mut self, value: i32) {
+        match value {
+            0 => self.output.push(0),
+            1..=122 => self.output.push(value as u8 + 5),
+            -123..=-1 => self.output.push((value - 5) as u8),
+            _ => {
+                let bytes = value.to_le_bytes();
+                let len = if value > 0 {
+                    bytes.iter().rposition(|&b| b != 0).unwrap_or(0) + 1
+                } else {
+                    bytes.iter().rposition(|&b| b != 0xFF).unwrap_or(0) + 1
+                };
+                let len_byte = if value > 0 {
+                    len as u8
+                } else {
+                    (len as u8).wrapping_neg()
+                };
+                self.output.push(len_byte);
+                self.output.extend_from_slice(&bytes[..len]);
+            }
+        }
+    }
+
+    fn write_len(&mut self, len: usize) -> Result<(), SerError> {
+        let len = i32::try_from(len).map_err(SerError::custom)?;
+        self.write_int(len);
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SerError> {
+        self.write_len(bytes.len())?;
+        self.output.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn write_symbol(&mut self, symbol: &alox_48::Symbol) -> Result<(), SerError> {
+        if let Some(&index) = self.symbols.get(symbol) {
+            self.output.push(b';');
+            self.write_len(index)
+        } else {
+            self.symbols.insert(symbol.clone(), self.symbols.len());
+            self.output.push(b':');
+            self.write_bytes(symbol.as_str().as_bytes())
+        }
+    }
+
+    fn write_fields(&mut self, fields: &alox_48::RbFields) -> Result<(), SerError> {
+        self.write_len(fields.len())?;
+        for (key, value) in fields {
+            self.write_symbol(key)?;
+            self.write_value(value, None)?;
+        }
+        Ok(())
+    }
+
+    fn write_hash(&mut self, hash: &alox_48::RbHash) -> Result<(), SerError> {
+        self.write_len(hash.len())?;
+        for (key, value) in hash {
+            self.write_value(key, None)?;
+            self.write_value(value, None)?;
+        }
+        Ok(())
+    }
+
+    /// `magnitude` is little endian, and may have trailing zeros.
+    fn write_bignum(&mut self, negative: bool, magnitude: &[u8]) -> Result<(), SerError> {
+        let significant = magnitude.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+        // the length is in 16 bit words
+        let words = significant.div_ceil(2);
+        self.output.push(b'l');
+        self.output.push(if negative { b'-' } else { b'+' });
+        self.write_len(words)?;
+        self.output.extend_from_slice(&magnitude[..significant]);
+        self.output
+            .resize(self.output.len() + words * 2 - significant, 0);
+        Ok(())
+    }
+
+    fn register(&mut self, id: Option<i32>) {
+        if let Some(id) = id {
+            self.ids.insert(id, self.object_count);
+        }
+        self.object_count += 1;
+    }
+
+    // `id` is the `$id` of this value, if it had one.
+    // It's recorded once the value is registered so values can link to themselves.
+    fn write_value(&mut self, value: &Value, id: Option<i32>) -> Result<(), SerError> {
+        match value {
+            Value::Object(object) if object.class.as_str() == ID_CLASS => {
+                let (id, value) = as_id(object).ok_or_else(|| SerError::custom("malformed $id"))?;
+                self.write_value(value, Some(id))?;
+            }
+            Value::Object(object) if object.class.as_str() == REF_CLASS => {
+                let id = as_ref(object).ok_or_else(|| SerError::custom("malformed $ref"))?;
+                let index = *self.ids.get(&id).ok_or_else(|| {
+                    SerError::custom(format!("$ref {id} does not refer to an earlier $id"))
+                })?;
+                self.output.push(b'@');
+                self.write_len(index)?;
+            }
+            Value::Object(object) if object.class.as_str() == BIGNUM_CLASS => {
+                let digits =
+                    as_bignum(object).ok_or_else(|| SerError::custom("malformed $bignum"))?;
+                let (negative, magnitude) = parse_bignum(digits)
+                    .ok_or_else(|| SerError::custom(format!("invalid $bignum {digits:?}")))?;
+                self.register(id);
+                self.write_bignum(negative, &magnitude)?;
+            }
+            Value::Object(object) if object.class.as_str() == HASH_DEFAULT_CLASS => {
+                let (hash, default) = as_hash_default(object)
+                    .ok_or_else(|| SerError::custom("malformed $hash_default"))?;
+                self.register(id);
+                self.output.push(b'}');
+                self.write_hash(hash)?;
+                self.write_value(default, None)?;
+            }
+            Value::Nil => self.output.push(b'0'),
+            Value::Bool(true) => self.output.push(b'T'),
+            Value::Bool(false) => self.output.push(b'F'),
+            Value::Integer(v) if !(FIXNUM_MIN..=FIXNUM_MAX).contains(v) => {
+                self.register(id);
+                let magnitude = i64::from(*v).unsigned_abs().to_le_bytes();
+                self.write_bignum(*v < 0, &magnitude)?;
+            }
+            Value::Integer(v) => {
+                self.output.push(b'i');
+                self.write_int(*v);
+            }
+            Value::Symbol(symbol) => self.write_symbol(symbol)?,
+            Value::Float(v) => {
+                self.register(id);
+                self.output.push(b'f');
+                self.write_bytes(&format_float(*v, self.floats))?;
+            }
+            Value::String(string) => {
+                self.register(id);
+                self.output.push(b'"');
+                self.write_bytes(&string.data)?;
+            }
+            Value::Regex { data, flags } => {
+                self.register(id);
+                self.output.push(b'/');
+                self.write_bytes(&data.data)?;
+                self.output.push(*flags);
+            }
+            Value::Class(name) | Value::Module(name) => {
+                self.register(id);
+                let tag = if matches!(value, Value::Class(_)) {
+                    b'c'
+                } else {
+                    b'm'
+                };
+                self.output.push(tag);
+                self.write_bytes(name.as_str().as_bytes())?;
+            }
+            Value::Array(array) => {
+                self.register(id);
+                self.output.push(b'[');
+                self.write_len(array.len())?;
+                for value in array {
+                    self.write_value(value, None)?;
+                }
+            }
+            Value::Hash(hash) => {
+                self.register(id);
+                self.output.push(b'{');
+                self.write_hash(hash)?;
+            }
+            Value::Object(alox_48::Object { class, fields }) => {
+                self.output.push(b'o');
+                self.write_symbol(class)?;
+                self.register(id);
+                self.write_fields(fields)?;
+            }
+            Value::RbStruct(alox_48::RbStruct { class, fields }) => {
+                self.output.push(b'S');
+                self.write_symbol(class)?;
+                self.register(id);
+                self.write_fields(fields)?;
+            }
+            Value::Userdata(alox_48::Userdata { class, data }) => {
+                self.output.push(b'u');
+                self.write_symbol(class)?;
+                self.write_bytes(data)?;
+                self.register(id);
+            }
+            Value::UserMarshal { class, value } => {
+                self.output.push(b'U');
+                self.write_symbol(class)?;
+                self.register(id);
+                self.write_value(value, None)?;
+            }
+            Value::Data { class, value } => {
+                self.output.push(b'd');
+                self.write_symbol(class)?;
+                self.register(id);
+                self.write_value(value, None)?;
+            }
+            Value::Extended { module, value } => {
+                self.output.push(b'e');
+                self.write_symbol(module)?;
+                self.write_value(value, id)?;
+            }
+            Value::UserClass { class, value } => {
+                self.output.push(b'C');
+                self.write_symbol(class)?;
+                self.write_value(value, id)?;
+            }
+            Value::Instance(alox_48::Instance { value, fields }) => {
+                self.output.push(b'I');
+                if let Value::Userdata(alox_48::Userdata { class, data }) = &**value {
+                    self.output.push(b'u');
+                    self.write_symbol(class)?;
+                    self.write_bytes(data)?;
+                    self.write_fields(fields)?;
+                    self.register(id);
+                } else {
+                    self.write_value(value, id)?;
+                    self.write_fields(fields)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Matches how Ruby formats floats in marshal data.
+fn format_float(v: f64, style: FloatStyle) -> Vec<u8> {
+    if v.is_nan() {
+        return b"nan".to_vec();
+    }
+    if v.is_infinite() {
+        return if v > 0.0 {
+            b"inf".to_vec()
+        } else {
+            b"-inf".to_vec()
+        };
+    }
+    if v == 0.0 {
+        return if v.is_sign_negative() {
+            b"-0".to_vec()
+        } else {
+            b"0".to_vec()
+        };
+    }
+
+    match style {
+        FloatStyle::Shortest => format_shortest(v).into_bytes(),
+        FloatStyle::Ruby18 => {
+            let mut out = format_ruby18(v).into_bytes();
+            out.extend(ruby18_mantissa(v));
+            out
+        }
+    }
+}
+
+fn format_shortest(v: f64) -> String {
+    // Rust's exponent formatting gives us the shortest digits that round trip, same as Ruby
+    let formatted = format!("{:e}", v.abs());
+    let (mantissa, exponent) = formatted
+        .split_once('e')
+        .expect("exponent formatting should contain an e");
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    let exponent: i32 = exponent.parse().expect("exponent should be an integer");
+    let decpt = exponent + 1;
+    let digs = digits.len() as i32;
+
+    let mut out = String::new();
+    if v < 0.0 {
+        out.push('-');
+    }
+    if decpt < -3 || decpt > digs {
+        out.push_str(&digits[..1]);
+        if digs > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        let _ = write!(out, "e{}", decpt - 1);
+    } else if decpt > 0 {
+        out.push_str(&digits[..decpt as usize]);
+        if digs > decpt {
+            out.push('.');
+            out.push_str(&digits[decpt as usize..]);
+        }
+    } else {
+        out.push_str("0.");
+        out.extend(std::iter::repeat_n('0', (-decpt) as usize));
+        out.push_str(&digits);
+    }
+    out
+}
+
+/// Strips trailing zeros from the fraction, like `%g` does.
+fn trim_fraction(number: &str) -> &str {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        number
+    }
+}
+
+// `%.17g`, which Ruby 1.8 formats floats with.
+fn format_ruby18(v: f64) -> String {
+    let scientific = format!("{v:.16e}");
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .expect("exponent formatting should contain an e");
+    let exponent: i32 = exponent.parse().expect("exponent should be an integer");
+
+    if (-4..17).contains(&exponent) {
+        let fixed = format!("{v:.*}", (16 - exponent) as usize);
+        trim_fraction(&fixed).to_owned()
+    } else {
+        let sign = if exponent < 0 { '-' } else { '+' };
+        format!(
+            "{}e{sign}{:02}",
+            trim_fraction(mantissa),
+            exponent.unsigned_abs()
+        )
+    }
+}
+
+// Ruby 1.8 follows the digits with a nul and the mantissa bits past the first
+// DBL_MANT_DIG - DBL_DIG (38), big endian with trailing zero bytes removed.
+// Nothing follows if those bits are all zero.
+fn ruby18_mantissa(v: f64) -> Vec<u8> {
+    let bits = v.abs().to_bits();
+    let fraction = bits & ((1 << 52) - 1);
+    // the mantissa normalized so its top bit is bit 52, like frexp
+    let mantissa = if bits >> 52 == 0 {
+        fraction << (fraction.leading_zeros() - 11)
+    } else {
+        fraction | 1 << 52
+    };
+
+    let rest = (mantissa & 0x7FFF) as u32;
+    if rest == 0 {
+        return vec![];
+    }
+    let mut out = vec![0];
+    out.extend((rest << 17).to_be_bytes());
+    while out.last() == Some(&0) {
+        out.pop();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marshal(body: &[u8]) -> Vec<u8> {
+        [&[4, 8], body].concat()
+    }
+
+    fn assert_round_trip(body: &[u8], floats: FloatStyle) -> Value {
+        let data = marshal(body);
+        let value = from_bytes(&data).expect("should deserialize");
+        let output = to_bytes(&value, floats).expect("should serialize");
+        assert_eq!(output, data);
+        value
+    }
+
+    #[test]
+    fn linked_string() {
+        // s = "a"; [s, s]
+        let value = assert_round_trip(b"[\x07\"\x06a@\x06", FloatStyle::Ruby18);
+        let string = Value::String(alox_48::RbString {
+            data: b"a".to_vec(),
+        });
+        assert_eq!(
+            value,
+            Value::Array(vec![id_object(1, string), ref_object(1)])
+        );
+    }
+
+    #[test]
+    fn link_numbering() {
+        // s = "x"; o = Foo.new(@s = s); [o, s, o]
+        let value = assert_round_trip(
+            b"[\x08o:\x08Foo\x06:\x07@s\"\x06x@\x07@\x06",
+            FloatStyle::Ruby18,
+        );
+        let Value::Array(array) = value else {
+            panic!("expected an array");
+        };
+        assert_eq!(array[1], ref_object(2));
+        assert_eq!(array[2], ref_object(1));
+    }
+
+    #[test]
+    fn symbols_are_not_objects() {
+        // s = "s"; [:a, :a, :b, s, :b, s]
+        let value = assert_round_trip(
+            b"[\x0b:\x06a;\x00:\x06b\"\x06s;\x06@\x06",
+            FloatStyle::Ruby18,
+        );
+        let Value::Array(array) = value else {
+            panic!("expected an array");
+        };
+        assert_eq!(array[1], Value::Symbol("a".into()));
+        assert_eq!(array[4], Value::Symbol("b".into()));
+        assert_eq!(array[5], ref_object(1));
+    }
+
+    #[test]
+    fn fixnums() {
+        let cases: &[(i32, &[u8])] = &[
+            (0, b"\x00"),
+            (1, b"\x06"),
+            (122, b"\x7f"),
+            (123, b"\x01\x7b"),
+            (255, b"\x01\xff"),
+            (256, b"\x02\x00\x01"),
+            (-1, b"\xfa"),
+            (-123, b"\x80"),
+            (-124, b"\xff\x84"),
+            (-256, b"\xff\x00"),
+            (-257, b"\xfe\xff\xfe"),
+            ((1 << 30) - 1, b"\x04\xff\xff\xff\x3f"),
+            (-(1 << 30), b"\xfc\x00\x00\x00\xc0"),
+        ];
+        for &(int, bytes) in cases {
+            let value = assert_round_trip(&[b"i", bytes].concat(), FloatStyle::Ruby18);
+            assert_eq!(value, Value::Integer(int), "{int}");
+        }
+    }
+
+    #[test]
+    fn bignums() {
+        // integers past the fixnum range are bignums, even if they fit in an i32
+        let cases: &[(i32, &[u8])] = &[
+            (1 << 30, b"+\x07\x00\x00\x00\x40"),
+            (-(1 << 30) - 1, b"-\x07\x01\x00\x00\x40"),
+            (i32::MAX, b"+\x07\xff\xff\xff\x7f"),
+            (i32::MIN, b"-\x07\x00\x00\x00\x80"),
+        ];
+        for &(int, bytes) in cases {
+            let value = assert_round_trip(&[b"l", bytes].concat(), FloatStyle::Ruby18);
+            assert_eq!(value, Value::Integer(int), "{int}");
+        }
+
+        let cases: &[(&str, &[u8])] = &[
+            ("1099511627776", b"+\x08\x00\x00\x00\x00\x00\x01"),
+            ("-1099511627776", b"-\x08\x00\x00\x00\x00\x00\x01"),
+            (
+                "18446744073709551616",
+                b"+\x0a\x00\x00\x00\x00\x00\x00\x00\x00\x01\x00",
+            ),
+        ];
+        for &(digits, bytes) in cases {
+            let value = assert_round_trip(&[b"l", bytes].concat(), FloatStyle::Ruby18);
+            assert_eq!(value, bignum_object(digits.to_owned()), "{digits}");
+        }
+
+        // bignums are objects, so they can be linked to
+        let value = assert_round_trip(
+            b"[\x07l+\x08\x00\x00\x00\x00\x00\x01@\x06",
+            FloatStyle::Ruby18,
+        );
+        let bignum = bignum_object("1099511627776".to_owned());
+        assert_eq!(
+            value,
+            Value::Array(vec![id_object(1, bignum), ref_object(1)])
+        );
+
+        let bad = bignum_object("12a".to_owned());
+        assert!(to_bytes(&bad, FloatStyle::Ruby18).is_err());
+    }
+
+    #[test]
+    fn hash_with_default() {
+        // h = Hash.new(0); h[1] = 2; [h, h]
+        let value = assert_round_trip(b"[\x07}\x06i\x06i\x07i\x00@\x06", FloatStyle::Ruby18);
+        let mut hash = alox_48::RbHash::new();
+        hash.insert(Value::Integer(1), Value::Integer(2));
+        let hash = hash_default_object(hash, Value::Integer(0));
+        assert_eq!(value, Value::Array(vec![id_object(1, hash), ref_object(1)]));
+    }
+
+    #[test]
+    fn linked_userdata() {
+        // u = Foo userdata; [u, u]
+        let value = assert_round_trip(b"[\x07u:\x08Foo\x07ab@\x06", FloatStyle::Ruby18);
+        let userdata = Value::Userdata(alox_48::Userdata {
+            class: "Foo".into(),
+            data: b"ab".to_vec(),
+        });
+        assert_eq!(
+            value,
+            Value::Array(vec![id_object(1, userdata), ref_object(1)])
+        );
+    }
+
+    #[test]
+    fn userdata_with_ivars_is_registered_after_them() {
+        // the ivar's string is object 1, and the userdata object 2
+        let value = assert_round_trip(
+            b"[\x07Iu:\x08Foo\x07ab\x06:\x07@e\"\x06z@\x07",
+            FloatStyle::Ruby18,
+        );
+        let Value::Array(array) = value else {
+            panic!("expected an array");
+        };
+        assert!(
+            matches!(&array[0], Value::Object(object) if as_id(object).is_some_and(|(id, _)| id == 2))
+        );
+        assert_eq!(array[1], ref_object(2));
+    }
+
+    #[test]
+    fn ruby18_floats() {
+        let cases: &[(f64, &[u8])] = &[
+            (1.1, b"1.1000000000000001\x0034"),
+            (0.1, b"0.10000000000000001\x0034"),
+            (100.0, b"100"),
+            (-2.5, b"-2.5"),
+            (1e16, b"10000000000000000"),
+            (1e17, b"1e+17\x00@"),
+            (1e20, b"1e+20\x00\x18\x80"),
+            (1.5e-5, b"1.5e-05\x00:\xd2"),
+            (0.333_333_333_333_333_3, b"0.33333333333333331\x00\xaa\xaa"),
+            (f64::MAX, b"1.7976931348623157e+308\x00\xff\xfe"),
+            (5e-324, b"4.9406564584124654e-324"),
+            (0.0, b"0"),
+            (-0.0, b"-0"),
+            (f64::INFINITY, b"inf"),
+            (f64::NEG_INFINITY, b"-inf"),
+        ];
+        for &(float, bytes) in cases {
+            assert_eq!(format_float(float, FloatStyle::Ruby18), bytes, "{float}");
+
+            let len = u8::try_from(bytes.len() + 5).expect("test floats are short");
+            let value = assert_round_trip(&[b"f", &[len], bytes].concat(), FloatStyle::Ruby18);
+            let Value::Float(read) = value else {
+                panic!("expected a float");
+            };
+            assert_eq!(read.to_bits(), float.to_bits(), "{float}");
+        }
+    }
+
+    #[test]
+    fn shortest_floats() {
+        let cases: &[(f64, &[u8])] = &[
+            (1.1, b"1.1"),
+            (100.0, b"1e2"),
+            (0.0001, b"0.0001"),
+            (1e-5, b"1e-5"),
+            (-2.5, b"-2.5"),
+            (f64::NAN, b"nan"),
+        ];
+        for &(float, bytes) in cases {
+            assert_eq!(format_float(float, FloatStyle::Shortest), bytes, "{float}");
+
+            let len = u8::try_from(bytes.len() + 5).expect("test floats are short");
+            assert_round_trip(&[b"f", &[len], bytes].concat(), FloatStyle::Shortest);
+        }
+    }
+}
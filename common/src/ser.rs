@@ -1,3 +1,4 @@
+use crate::links;
 use alox_48::Value;
 use serde::ser::{SerializeMap, SerializeSeq};
 
@@ -169,7 +170,52 @@ struct SerializeUsertype<'a> {
     value: SerializeValue<'a>,
 }
 
+#[derive(serde::Serialize)]
+#[serde(rename = "Linked")]
+struct SerializeLinked<'a> {
+    id: i32,
+    value: SerializeValue<'a>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename = "HashDefault")]
+struct SerializeHashDefault<'a> {
+    hash: SerializeHash<'a>,
+    default: SerializeValue<'a>,
+}
+
+/// One of the reserved classes [`links`] uses.
+struct SerializeReserved<'a>(&'a alox_48::Object);
+
+impl serde::Serialize for SerializeReserved<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        if let Some((id, value)) = links::as_id(self.0) {
+            map.serialize_key("$id")?;
+            map.serialize_value(&SerializeLinked {
+                id,
+                value: SerializeValue(value),
+            })?;
+        } else if let Some(id) = links::as_ref(self.0) {
+            map.serialize_entry("$ref", &id)?;
+        } else if let Some(digits) = links::as_bignum(self.0) {
+            map.serialize_entry("$bignum", digits)?;
+        } else if let Some((hash, default)) = links::as_hash_default(self.0) {
+            map.serialize_key("$hash_default")?;
+            map.serialize_value(&SerializeHashDefault {
+                hash: SerializeHash(hash),
+                default: SerializeValue(default),
+            })?;
+        }
+        map.end()
+    }
+}
+
 impl serde::Serialize for SerializeValue<'_> {
+    #[allow(clippy::too_many_lines)]
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
@@ -198,6 +244,9 @@ impl serde::Serialize for SerializeValue<'_> {
                 map.serialize_value::<SerializeUserdata<'_>>(&userdata.into())?;
                 map.end()
             }
+            Value::Object(object) if links::is_reserved(object) => {
+                SerializeReserved(object).serialize(serializer)
+            }
             Value::Object(object) => {
                 let mut map = serializer.serialize_map(Some(1))?;
                 map.serialize_key("$object")?;
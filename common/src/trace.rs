@@ -0,0 +1,170 @@
+//! Where in the data an error happened.
+
+use alox_48::path_to_error::Context;
+use std::fmt::Write;
+
+/// The location of an error inside the data being converted.
+#[derive(Debug, Clone, Default)]
+pub struct Trace {
+    /// Like `events[12].pages[1].list[40]`. Empty if the error is at the top level.
+    pub path: String,
+    /// Byte offset into marshal data.
+    ///
+    /// Errors from text formats already say which line and column they happened at.
+    pub offset: Option<usize>,
+}
+
+impl Trace {
+    pub fn is_empty(&self) -> bool {
+        self.path.is_empty() && self.offset.is_none()
+    }
+}
+
+impl std::fmt::Display for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.path.as_str(), self.offset) {
+            ("", Some(offset)) => write!(f, "byte {offset}"),
+            (path, Some(offset)) => write!(f, "{path}, byte {offset}"),
+            (path, None) => f.write_str(path),
+        }
+    }
+}
+
+fn unwrap(value: &alox_48::Value) -> &alox_48::Value {
+    match value {
+        alox_48::Value::Instance(instance) => unwrap(&instance.value),
+        alox_48::Value::Extended { value, .. } | alox_48::Value::UserClass { value, .. } => {
+            unwrap(value)
+        }
+        value => value,
+    }
+}
+
+fn hash_key(key: &alox_48::Value) -> Option<String> {
+    match unwrap(key) {
+        alox_48::Value::Integer(i) => Some(i.to_string()),
+        alox_48::Value::String(s) => Some(format!("{:?}", s.to_string_lossy())),
+        alox_48::Value::Symbol(s) => Some(format!(":{}", s.as_str())),
+        _ => None,
+    }
+}
+
+/// Which way the data was being converted when the trace was made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    Reading,
+    Writing,
+}
+
+/// Turns an alox trace into a path like `events[12].pages[1]`.
+///
+/// `root` is the same data read as a loose value, if possible. It's used to look up hash keys,
+/// which the trace only has the index of. Entries whose key can't be found are written as `[#index]`.
+pub(crate) fn marshal_path(
+    trace: &alox_48::path_to_error::Trace,
+    root: Option<&alox_48::Value>,
+    direction: Direction,
+) -> String {
+    let mut path = String::new();
+    let mut current = root;
+
+    // alox's deserializer counts an element as soon as it starts reading it, so its indices are
+    // one past the element. The serializer's are the element's own.
+    let element = |index: usize| match direction {
+        Direction::Reading => index.saturating_sub(1),
+        Direction::Writing => index,
+    };
+
+    // the innermost context comes first
+    for context in trace.context.iter().rev() {
+        let value = current.map(unwrap);
+        match context {
+            Context::ArrayIndex(index) => {
+                let index = element(*index);
+                let _ = write!(path, "[{index}]");
+                current = value
+                    .and_then(alox_48::Value::as_array)
+                    .and_then(|array| array.get(index));
+            }
+            Context::HashValue(index) | Context::HashKey(index) => {
+                let index = element(*index);
+                let entry = value
+                    .and_then(alox_48::Value::as_hash)
+                    .and_then(|hash| hash.get_index(index));
+                if let Some((key, entry_value)) =
+                    entry.and_then(|(key, value)| Some((hash_key(key)?, value)))
+                {
+                    let _ = write!(path, "[{key}]");
+                    current = Some(entry_value);
+                } else {
+                    let _ = write!(path, "[#{index}]");
+                    current = None;
+                }
+            }
+            Context::Field(field, index) => {
+                if !path.is_empty() {
+                    path.push('.');
+                }
+                if let Some(field) = field {
+                    path.push_str(field.as_str().trim_start_matches('@'));
+                    current = value
+                        .and_then(alox_48::Value::as_object)
+                        .and_then(|object| object.fields.get(field));
+                } else {
+                    let _ = write!(path, "<field {}>", element(*index));
+                    current = None;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    path
+}
+
+/// The path of the value the byte at `offset` in marshal `data` belongs to, like `events[12].pages[1]`.
+///
+/// Empty if it belongs to the top level value, and `None` if `offset` is past the end of the data.
+pub fn marshal_path_at(data: &[u8], offset: usize) -> Option<String> {
+    if offset >= data.len() {
+        return None;
+    }
+    // reading the data cut off at `offset` fails inside the value that byte belongs to
+    let Ok(mut deserializer) = alox_48::Deserializer::new(&data[..offset]) else {
+        return Some(String::new());
+    };
+    match alox_48::path_to_error::deserialize::<alox_48::Value>(&mut deserializer) {
+        Ok(_) => Some(String::new()),
+        Err((_, trace)) => {
+            let root = alox_48::from_bytes::<alox_48::Value>(data).ok();
+            Some(marshal_path(&trace, root.as_ref(), Direction::Reading))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reading_indices() {
+        // [1, [2, 3]], with the offset at the 3
+        let data = b"\x04\x08[\x07i\x06[\x07i\x07i\x08";
+        assert_eq!(marshal_path_at(data, 10).as_deref(), Some("[1][1]"));
+        assert_eq!(marshal_path_at(data, 4).as_deref(), Some("[0]"));
+        assert_eq!(marshal_path_at(data, data.len()), None);
+    }
+
+    #[test]
+    fn writing_indices() {
+        // what alox's serializer traces when the third element of a hash's first value fails
+        let mut trace = alox_48::path_to_error::Trace::new();
+        trace.context = vec![
+            Context::ArrayIndex(2),
+            Context::Array(3),
+            Context::HashValue(0),
+            Context::Hash(1),
+        ];
+        assert_eq!(marshal_path(&trace, None, Direction::Writing), "[#0][2]");
+    }
+}
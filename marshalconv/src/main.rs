@@ -4,27 +4,42 @@ extern crate rpgtool_common as common;
 
 use clap::{CommandFactory, Parser, error::ErrorKind};
 use common::Format;
-use std::path::PathBuf;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 
-// TODO stdin?
 /// Converts Ruby marshal files to other formats, and vice versa.
 #[derive(Parser)]
 struct Cli {
     #[arg(long, exclusive = true)]
     completions: Option<clap_complete::Shell>,
-    /// The source file.
+    /// The source file, or `-` for stdin.
     #[arg(value_hint = clap_complete::ValueHint::FilePath, required_unless_present="completions")]
     src: Option<PathBuf>,
-    /// The destination file.
+    /// The destination file, or `-` for stdout.
     #[arg(value_hint = clap_complete::ValueHint::FilePath, required_unless_present="completions")]
     dest: Option<PathBuf>,
     /// The formats to convert from/to.
     ///
     /// Input comes first.
     ///
-    /// Required if the format cannot be determined via file extensions.
+    /// Required if the format cannot be determined via file extensions, or when using stdin/stdout.
     #[arg(long, visible_short_alias = 'f', number_of_values = 2)]
     format: Option<Vec<Format>>,
+    /// Preserve object links (shared objects) as `$id`/`$ref` pairs.
+    ///
+    /// Without this, shared objects are duplicated when converting from marshal.
+    #[arg(long)]
+    links: bool,
+    /// Read every value in the source instead of just the first.
+    ///
+    /// Marshal is read as consecutive dumps, like a file written with several `Marshal.dump`
+    /// calls. Values are written as a top-level sequence, or as one document per value in YAML.
+    #[arg(long)]
+    multi: bool,
+}
+
+fn is_std(path: &Path) -> bool {
+    path == Path::new("-")
 }
 
 fn main() {
@@ -33,6 +48,8 @@ fn main() {
         src,
         dest,
         format,
+        links,
+        multi,
     } = Cli::parse();
 
     if let Some(shell) = completions {
@@ -47,6 +64,15 @@ fn main() {
 
     let [from, to] = match format.as_deref() {
         Some(&[from, to]) => [from, to],
+        None if is_std(&src) || is_std(&dest) => {
+            let mut command = Cli::command();
+            command
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "--format is required when reading from stdin or writing to stdout",
+                )
+                .exit()
+        }
         None => {
             let Some((from, to)) = Format::guess(&src).zip(Format::guess(&dest)) else {
                 let mut command = Cli::command();
@@ -62,29 +88,63 @@ fn main() {
         _ => unreachable!(), // we enforce the number of values in clap
     };
 
-    let input = match std::fs::File::open(&src) {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("failed to open {}: {e}", src.display());
-            return;
+    let src_name = if is_std(&src) {
+        "stdin".into()
+    } else {
+        src.display().to_string()
+    };
+
+    let input: Box<dyn Read> = if is_std(&src) {
+        Box::new(std::io::stdin().lock())
+    } else {
+        match std::fs::File::open(&src) {
+            Ok(f) => Box::new(f),
+            Err(e) => {
+                eprintln!("failed to open {src_name}: {e}");
+                return;
+            }
         }
     };
-    let value: common::Value = match common::conv_read(from, input) {
+    // a single value is converted as a list of one
+    let result = match (multi, links) {
+        (true, true) => common::conv_read_linked_multi(from, input),
+        (true, false) => common::conv_read_multi(from, input),
+        (false, true) => common::conv_read_linked(from, input).map(|value| vec![value]),
+        (false, false) => common::conv_read(from, input).map(|value| vec![value]),
+    };
+    let values: Vec<common::Value> = match result {
         Ok(v) => v,
         Err(e) => {
-            eprintln!("failed to parse {}: {e}", src.display());
+            eprintln!("failed to parse {src_name}: {e}");
             return;
         }
     };
 
-    let output = match std::fs::File::create(&dest) {
-        Ok(f) => f,
-        Err(e) => {
-            eprintln!("failed to open {}: {e}", dest.display());
-            return;
+    let output: Box<dyn Write> = if is_std(&dest) {
+        Box::new(std::io::stdout().lock())
+    } else {
+        match std::fs::File::create(&dest) {
+            Ok(f) => Box::new(f),
+            Err(e) => {
+                eprintln!("failed to open {}: {e}", dest.display());
+                return;
+            }
         }
     };
-    if let Err(e) = common::conv_write(value, to, output) {
-        eprintln!("failed to convert {}: {e}", src.display());
+    let mut output = std::io::BufWriter::new(output);
+    // RPG Maker XP and VX wrote floats the way Ruby 1.8 does
+    let floats = common::links::FloatStyle::guess(&dest);
+    let result = match (multi, links) {
+        (true, true) => common::conv_write_linked_multi(&values, to, floats, &mut output),
+        (true, false) => common::conv_write_multi(&values, to, &mut output),
+        (false, true) => common::conv_write_linked(&values[0], to, floats, &mut output),
+        (false, false) => common::conv_write(&values[0], to, &mut output),
+    };
+    if let Err(e) = result {
+        eprintln!("failed to convert {src_name}: {e}");
+        return;
+    }
+    if let Err(e) = output.flush() {
+        eprintln!("failed to write {}: {e}", dest.display());
     }
 }
@@ -0,0 +1,282 @@
+use clap::{CommandFactory, error::ErrorKind};
+use common::Format;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+
+use crate::{
+    ArchiveArgs, ArchiveCatArgs, ArchiveCommand, ArchiveExtractArgs, ArchiveListArgs,
+    ArchivePackArgs, Cli, GameVer,
+};
+use crate::{structured, walk};
+
+mod rgssad;
+use rgssad::Source;
+pub use rgssad::Version;
+
+pub fn archive(args: ArchiveArgs) {
+    let result = match args.command {
+        ArchiveCommand::Extract(args) => extract(args),
+        ArchiveCommand::Pack(args) => pack(args),
+        ArchiveCommand::List(args) => list(&args),
+        ArchiveCommand::Cat(args) => cat(args),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+type FileArchive = rgssad::Archive<std::io::BufReader<std::fs::File>>;
+
+fn open(path: &Path) -> Result<FileArchive, String> {
+    let file =
+        std::fs::File::open(path).map_err(|e| format!("couldn't open {}: {e}", path.display()))?;
+    rgssad::Archive::open(std::io::BufReader::new(file))
+        .map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn resolve_game_version(game_version: Option<GameVer>, archive: &Path) -> GameVer {
+    let game_version = game_version.or_else(|| {
+        archive
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(structured::detect::from_ext)
+    });
+    let Some(game_version) = game_version else {
+        let mut command = Cli::command();
+        command
+            .error(
+                ErrorKind::DisplayHelp,
+                "unable to determine game version, please specify it",
+            )
+            .exit()
+    };
+    game_version
+}
+
+/// Where an entry goes in `dest`. Names that would end up outside of it are refused.
+fn entry_path(dest: &Path, name: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(name);
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "refusing to extract {name}, it's outside the destination"
+        ));
+    }
+    Ok(dest.join(relative))
+}
+
+/// Whether an entry is game data, which `--to` converts.
+fn is_data_file(name: &str) -> bool {
+    name.starts_with("Data/") && Format::guess(Path::new(name)) == Some(Format::Marshal)
+}
+
+fn extract(args: ArchiveExtractArgs) -> Result<(), String> {
+    let ArchiveExtractArgs {
+        archive: archive_path,
+        dest,
+        game_version,
+        to,
+    } = args;
+
+    // data files are already marshal
+    let to = to.filter(|&to| to != Format::Marshal);
+    let convert = to.map(|to| (resolve_game_version(game_version, &archive_path), to));
+
+    let mut archive = open(&archive_path)?;
+    let entries = std::mem::take(&mut archive.entries);
+
+    let mut failed = 0;
+    for entry in &entries {
+        if let Err(e) = extract_entry(&mut archive, entry, &dest, convert) {
+            eprintln!("{e}");
+            failed += 1;
+        }
+    }
+
+    eprintln!(
+        "[INFO]: Extracted {} file(s) to {}",
+        entries.len() - failed,
+        dest.display()
+    );
+    if failed > 0 {
+        return Err(format!("failed to extract {failed} file(s)"));
+    }
+    Ok(())
+}
+
+fn extract_entry(
+    archive: &mut FileArchive,
+    entry: &rgssad::Entry,
+    dest: &Path,
+    convert: Option<(GameVer, Format)>,
+) -> Result<(), String> {
+    let mut path = entry_path(dest, &entry.name)?;
+    let mut data = archive.read(entry)?;
+
+    if let Some((game_version, to)) = convert
+        && is_data_file(&entry.name)
+    {
+        let filename = path
+            .file_prefix()
+            .and_then(|prefix| prefix.to_str())
+            .unwrap_or_default();
+        data = structured::convert_bytes(game_version, filename, &data, Format::Marshal, to)
+            .map_err(|e| format!("failed to convert {}: {e}", entry.name))?;
+        path.set_extension(to.file_ext());
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+    }
+    std::fs::write(&path, data).map_err(|e| format!("couldn't write {}: {e}", path.display()))
+}
+
+fn pack(args: ArchivePackArgs) -> Result<(), String> {
+    let ArchivePackArgs {
+        project,
+        archive: archive_path,
+        version,
+        mut include,
+        exclude,
+    } = args;
+
+    let version = version.or_else(|| match archive_path.extension()?.to_str()? {
+        "rgssad" | "rgss2a" => Some(Version::V1),
+        "rgss3a" => Some(Version::V3),
+        _ => None,
+    });
+    let Some(version) = version else {
+        let mut command = Cli::command();
+        command
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "unable to determine archive version, please specify it with --archive-version",
+            )
+            .exit()
+    };
+
+    // what the editor puts in an archive
+    if include.is_empty() {
+        include = vec!["Data/**".to_string(), "Graphics/**".to_string()];
+    }
+    let filter = walk::Filter::new(&include, &exclude).map_err(|e| format!("invalid glob: {e}"))?;
+    let entries = walk::entries(&project, true, &filter, true)?;
+
+    // an archive in the project shouldn't end up in itself
+    let archive_canonical = std::fs::canonicalize(&archive_path).ok();
+    let mut files = vec![];
+    for entry in entries {
+        if archive_canonical.is_some()
+            && std::fs::canonicalize(&entry.path).ok() == archive_canonical
+        {
+            continue;
+        }
+
+        let name = entry
+            .relative
+            .to_str()
+            .ok_or_else(|| format!("{} is not valid UTF-8", entry.relative.display()))?
+            .replace('\\', "/");
+        let size = std::fs::metadata(&entry.path)
+            .map_err(|e| format!("couldn't open {}: {e}", entry.path.display()))?
+            .len();
+        let size = u32::try_from(size)
+            .map_err(|_| format!("{} is too large for an archive", entry.path.display()))?;
+        files.push(Source {
+            name,
+            path: entry.path,
+            size,
+        });
+    }
+
+    let file = std::fs::File::create(&archive_path)
+        .map_err(|e| format!("couldn't create {}: {e}", archive_path.display()))?;
+    let mut writer = std::io::BufWriter::new(file);
+    let result = rgssad::write(&mut writer, version, &files)
+        .and_then(|()| writer.flush().map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&archive_path);
+        return Err(format!("failed to write {}: {e}", archive_path.display()));
+    }
+
+    eprintln!(
+        "[INFO]: Packed {} file(s) into {}",
+        files.len(),
+        archive_path.display()
+    );
+    Ok(())
+}
+
+fn list(args: &ArchiveListArgs) -> Result<(), String> {
+    let archive = open(&args.archive)?;
+
+    let mut output = std::io::BufWriter::new(std::io::stdout().lock());
+    writeln!(
+        output,
+        "{:>10}  {:>10}  {:>10}  name",
+        "size", "offset", "key"
+    )
+    .map_err(|e| e.to_string())?;
+    for entry in &archive.entries {
+        writeln!(
+            output,
+            "{:>10}  {:>10}  0x{:08x}  {}",
+            entry.size, entry.offset, entry.key, entry.name
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    output.flush().map_err(|e| e.to_string())
+}
+
+fn cat(args: ArchiveCatArgs) -> Result<(), String> {
+    let ArchiveCatArgs {
+        archive: archive_path,
+        name,
+        game_version,
+        to,
+    } = args;
+
+    let to = to.filter(|&to| to != Format::Marshal);
+    let game_version = to.map(|_| resolve_game_version(game_version, &archive_path));
+
+    let mut archive = open(&archive_path)?;
+    let name = name.replace('\\', "/");
+    // the game looks files up case insensitively
+    let index = archive
+        .entries
+        .iter()
+        .position(|entry| entry.name == name)
+        .or_else(|| {
+            archive
+                .entries
+                .iter()
+                .position(|entry| entry.name.eq_ignore_ascii_case(&name))
+        })
+        .ok_or_else(|| format!("{name} is not in {}", archive_path.display()))?;
+    let entry = archive.entries.swap_remove(index);
+    let mut data = archive.read(&entry)?;
+
+    if let Some((game_version, to)) = game_version.zip(to) {
+        let filename = Path::new(&entry.name)
+            .file_prefix()
+            .and_then(|prefix| prefix.to_str())
+            .unwrap_or_default();
+        data = structured::convert_bytes(game_version, filename, &data, Format::Marshal, to)
+            .map_err(|e| format!("failed to convert {}: {e}", entry.name))?;
+        // JSON and RON don't end with a newline
+        if to != Format::Yaml {
+            data.push(b'\n');
+        }
+    }
+
+    let mut output = std::io::stdout().lock();
+    output
+        .write_all(&data)
+        .and_then(|()| output.flush())
+        .map_err(|e| e.to_string())
+}
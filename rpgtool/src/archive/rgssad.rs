@@ -0,0 +1,393 @@
+//! The encrypted archives RPG Maker games are shipped in.
+//!
+//! Version 1 (`.rgssad` and `.rgss2a`) interleaves the index with the file data, and encrypts it
+//! with one key that changes after every value.
+//! Version 3 (`.rgss3a`) has a key in the header, and the whole index before the file data.
+//!
+//! File data is encrypted by XOR, 4 bytes at a time, with a key that changes after every 4 bytes.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+
+const MAGIC: &[u8; 7] = b"RGSSAD\0";
+const V1_KEY: u32 = 0xDEAD_CAFE;
+// the editor picks a random one, but we want the same project to always give the same archive
+const V3_SEED: u32 = 0xDEAD_CAFE;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Version {
+    /// Used by RPG Maker XP and VX.
+    #[value(name = "1")]
+    V1,
+    /// Used by RPG Maker VX Ace.
+    #[value(name = "3")]
+    V3,
+}
+
+pub struct Entry {
+    /// With `/` separators, like `Data/Map001.rxdata`.
+    pub name: String,
+    pub offset: u64,
+    pub size: u32,
+    /// The key the file's data is encrypted with.
+    pub key: u32,
+}
+
+fn next_key(key: u32) -> u32 {
+    key.wrapping_mul(7).wrapping_add(3)
+}
+
+/// Encrypts or decrypts file data. It's an XOR, so both are the same.
+pub fn crypt(data: &mut [u8], mut key: u32) {
+    for chunk in data.chunks_mut(4) {
+        for (byte, key_byte) in chunk.iter_mut().zip(key.to_le_bytes()) {
+            *byte ^= key_byte;
+        }
+        key = next_key(key);
+    }
+}
+
+pub struct Archive<R> {
+    reader: R,
+    pub entries: Vec<Entry>,
+}
+
+impl<R: Read + Seek> Archive<R> {
+    /// Reads the archive's index.
+    pub fn open(mut reader: R) -> Result<Self, String> {
+        let mut header = [0; 8];
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| "not an RGSSAD archive".to_string())?;
+        if &header[..7] != MAGIC {
+            return Err("not an RGSSAD archive".to_string());
+        }
+
+        let entries = match header[7] {
+            1 => read_v1_index(&mut reader),
+            3 => read_v3_index(&mut reader),
+            version => return Err(format!("unsupported archive version {version}")),
+        };
+        let entries = entries.map_err(|e| format!("failed to read index: {e}"))?;
+
+        Ok(Self { reader, entries })
+    }
+
+    /// Reads and decrypts a file.
+    pub fn read(&mut self, entry: &Entry) -> Result<Vec<u8>, String> {
+        let mut data = vec![0; entry.size as usize];
+        self.reader
+            .seek(SeekFrom::Start(entry.offset))
+            .and_then(|_| self.reader.read_exact(&mut data))
+            .map_err(|e| format!("failed to read {}: {e}", entry.name))?;
+        crypt(&mut data, entry.key);
+        Ok(data)
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> std::io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_name(reader: &mut impl Read, len: u32, remaining: u64) -> std::io::Result<Vec<u8>> {
+    // a corrupt length shouldn't make us allocate gigabytes
+    if u64::from(len) > remaining {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    let mut name = vec![0; len as usize];
+    reader.read_exact(&mut name)?;
+    Ok(name)
+}
+
+fn decode_name(name: &[u8]) -> String {
+    String::from_utf8_lossy(name).replace('\\', "/")
+}
+
+fn read_v1_index<R: Read + Seek>(reader: &mut R) -> std::io::Result<Vec<Entry>> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    let mut position = reader.seek(SeekFrom::Start(8))?;
+
+    let mut key = V1_KEY;
+    let mut entries = vec![];
+    while position < len {
+        let name_len = read_u32(reader)? ^ key;
+        key = next_key(key);
+
+        let mut name = read_name(reader, name_len, len - position)?;
+        for byte in &mut name {
+            *byte ^= key.to_le_bytes()[0];
+            key = next_key(key);
+        }
+
+        let size = read_u32(reader)? ^ key;
+        key = next_key(key);
+
+        let offset = reader.stream_position()?;
+        if offset + u64::from(size) > len {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        entries.push(Entry {
+            name: decode_name(&name),
+            offset,
+            size,
+            key,
+        });
+        position = reader.seek(SeekFrom::Current(i64::from(size)))?;
+    }
+
+    Ok(entries)
+}
+
+fn read_v3_index<R: Read + Seek>(reader: &mut R) -> std::io::Result<Vec<Entry>> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(8))?;
+
+    let key = read_u32(reader)?.wrapping_mul(9).wrapping_add(3);
+    let mut entries = vec![];
+    loop {
+        let offset = read_u32(reader)? ^ key;
+        // the index ends with an entry at offset 0
+        if offset == 0 {
+            break;
+        }
+        let size = read_u32(reader)? ^ key;
+        let file_key = read_u32(reader)? ^ key;
+        let name_len = read_u32(reader)? ^ key;
+
+        let position = reader.stream_position()?;
+        let mut name = read_name(reader, name_len, len.saturating_sub(position))?;
+        for (i, byte) in name.iter_mut().enumerate() {
+            *byte ^= key.to_le_bytes()[i % 4];
+        }
+
+        if u64::from(offset) + u64::from(size) > len {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        entries.push(Entry {
+            name: decode_name(&name),
+            offset: offset.into(),
+            size,
+            key: file_key,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// A file to put in an archive.
+pub struct Source {
+    /// With `/` separators, like `Data/Map001.rxdata`.
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u32,
+}
+
+impl Source {
+    fn encoded_name(&self) -> Vec<u8> {
+        self.name.replace('/', "\\").into_bytes()
+    }
+
+    /// Reads and encrypts the file.
+    fn read(&self, key: u32) -> Result<Vec<u8>, String> {
+        let mut data = std::fs::read(&self.path)
+            .map_err(|e| format!("couldn't open {}: {e}", self.path.display()))?;
+        if data.len() != self.size as usize {
+            return Err(format!("{} changed while packing", self.path.display()));
+        }
+        crypt(&mut data, key);
+        Ok(data)
+    }
+}
+
+fn write_u32(writer: &mut impl Write, value: u32) -> Result<(), String> {
+    writer
+        .write_all(&value.to_le_bytes())
+        .map_err(|e| e.to_string())
+}
+
+fn write_bytes(writer: &mut impl Write, bytes: &[u8]) -> Result<(), String> {
+    writer.write_all(bytes).map_err(|e| e.to_string())
+}
+
+/// Writes an archive with `files`, in the order given.
+pub fn write(writer: &mut impl Write, version: Version, files: &[Source]) -> Result<(), String> {
+    write_bytes(writer, MAGIC)?;
+    match version {
+        Version::V1 => write_v1(writer, files),
+        Version::V3 => write_v3(writer, files),
+    }
+}
+
+fn write_v1(writer: &mut impl Write, files: &[Source]) -> Result<(), String> {
+    write_bytes(writer, &[1])?;
+
+    let mut key = V1_KEY;
+    for file in files {
+        let mut name = file.encoded_name();
+        let name_len = u32::try_from(name.len()).map_err(|_| "file name is too long")?;
+        write_u32(writer, name_len ^ key)?;
+        key = next_key(key);
+
+        for byte in &mut name {
+            *byte ^= key.to_le_bytes()[0];
+            key = next_key(key);
+        }
+        write_bytes(writer, &name)?;
+
+        write_u32(writer, file.size ^ key)?;
+        key = next_key(key);
+
+        write_bytes(writer, &file.read(key)?)?;
+    }
+
+    Ok(())
+}
+
+fn write_v3(writer: &mut impl Write, files: &[Source]) -> Result<(), String> {
+    write_bytes(writer, &[3])?;
+    write_u32(writer, V3_SEED)?;
+    let key = V3_SEED.wrapping_mul(9).wrapping_add(3);
+
+    let names: Vec<_> = files.iter().map(Source::encoded_name).collect();
+    // the header, seed, index entries, and the empty entry ending the index
+    let index_len: usize = names.iter().map(|name| 16 + name.len()).sum();
+    let too_large = || "archive would be larger than 4 GiB".to_string();
+    let mut offset = u32::try_from(12 + index_len + 16).map_err(|_| too_large())?;
+
+    let mut file_keys = vec![];
+    let mut file_key = key;
+    for (file, name) in files.iter().zip(&names) {
+        file_key = next_key(file_key);
+        file_keys.push(file_key);
+
+        let name_len = u32::try_from(name.len()).map_err(|_| "file name is too long")?;
+        for value in [offset, file.size, file_key, name_len] {
+            write_u32(writer, value ^ key)?;
+        }
+        let name: Vec<_> = name
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| byte ^ key.to_le_bytes()[i % 4])
+            .collect();
+        write_bytes(writer, &name)?;
+
+        offset = offset.checked_add(file.size).ok_or_else(too_large)?;
+    }
+    for _ in 0..4 {
+        write_u32(writer, key)?;
+    }
+
+    for (file, file_key) in files.iter().zip(file_keys) {
+        write_bytes(writer, &file.read(file_key)?)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const FILES: [(&str, &[u8]); 2] = [
+        ("Data/Actors.rxdata", b"hello, world!"),
+        ("Graphics/a.png", b"\x89PNG"),
+    ];
+
+    // built from the format description by a separate implementation, not by `write`
+    const V1_FIXTURE: &[u8] = &[
+        0x52, 0x47, 0x53, 0x53, 0x41, 0x44, 0x00, 0x01, 0xec, 0xca, 0xad, 0xde, 0xb1, 0xd7, 0x89,
+        0x8f, 0xd9, 0xe7, 0xee, 0xaa, 0x7a, 0xe4, 0x6e, 0xe0, 0xd7, 0xfe, 0xc9, 0xdf, 0x41, 0x17,
+        0x30, 0xc1, 0x59, 0x7f, 0xc6, 0x2d, 0x18, 0x17, 0xaa, 0xd0, 0x0d, 0x17, 0x09, 0x9b, 0x2d,
+        0xc5, 0xec, 0xa0, 0x48, 0x74, 0x7b, 0x82, 0x14, 0xac, 0xee, 0x3d, 0x3f, 0x3e, 0xfd, 0xb9,
+        0x27, 0xc3, 0x0e, 0x1b, 0x51, 0x79, 0xe7, 0x48, 0xe9, 0xe7, 0x04, 0xb0, 0x27,
+    ];
+    // seed 0x1234, with file keys 0xabcd and 0xac1a
+    const V3_FIXTURE: &[u8] = &[
+        0x52, 0x47, 0x53, 0x53, 0x41, 0x44, 0x00, 0x03, 0x34, 0x12, 0x00, 0x00, 0x8b, 0xa3, 0x00,
+        0x00, 0xda, 0xa3, 0x00, 0x00, 0x1a, 0x08, 0x00, 0x00, 0xc5, 0xa3, 0x00, 0x00, 0x93, 0xc2,
+        0x74, 0x61, 0x8b, 0xe2, 0x63, 0x74, 0xb8, 0xd1, 0x73, 0x2e, 0xa5, 0xdb, 0x64, 0x61, 0xa3,
+        0xc2, 0xbe, 0xa3, 0x00, 0x00, 0xd3, 0xa3, 0x00, 0x00, 0xcd, 0x0f, 0x00, 0x00, 0xd9, 0xa3,
+        0x00, 0x00, 0x90, 0xd1, 0x61, 0x70, 0xbf, 0xca, 0x63, 0x73, 0x8b, 0xc2, 0x2e, 0x70, 0xb9,
+        0xc4, 0xd7, 0xa3, 0x00, 0x00, 0xd7, 0xa3, 0x00, 0x00, 0xd7, 0xa3, 0x00, 0x00, 0xd7, 0xa3,
+        0x00, 0x00, 0xa5, 0xce, 0x6c, 0x6c, 0xf1, 0x9e, 0x24, 0x77, 0x3a, 0x90, 0x4c, 0x64, 0x77,
+        0x93, 0xfc, 0x4e, 0x47,
+    ];
+
+    fn sources(version: Version) -> Vec<Source> {
+        let dir =
+            std::env::temp_dir().join(format!("rpgtool-rgssad-{}-{version:?}", std::process::id()));
+        FILES
+            .iter()
+            .enumerate()
+            .map(|(i, (name, data))| {
+                std::fs::create_dir_all(&dir).expect("temp dir is writable");
+                let path = dir.join(i.to_string());
+                std::fs::write(&path, data).expect("temp dir is writable");
+                Source {
+                    name: name.to_string(),
+                    path,
+                    size: u32::try_from(data.len()).expect("fixture is small"),
+                }
+            })
+            .collect()
+    }
+
+    fn pack(version: Version) -> Vec<u8> {
+        let sources = sources(version);
+        let mut archive = vec![];
+        write(&mut archive, version, &sources).expect("sources are readable");
+        for source in sources {
+            std::fs::remove_file(source.path).expect("source exists");
+        }
+        archive
+    }
+
+    fn check(archive: &[u8]) {
+        let mut archive = Archive::open(Cursor::new(archive)).expect("archive is valid");
+        let entries = std::mem::take(&mut archive.entries);
+        assert_eq!(entries.len(), FILES.len());
+        for (entry, (name, data)) in entries.iter().zip(FILES) {
+            assert_eq!(entry.name, name);
+            assert_eq!(entry.size as usize, data.len());
+            assert_eq!(archive.read(entry).expect("entry is readable"), data);
+        }
+    }
+
+    #[test]
+    fn crypt_round_trip() {
+        let mut data = *b"hello, world!";
+        crypt(&mut data, V1_KEY);
+        assert_eq!(&data[..4], &[0x96, 0xaf, 0xc1, 0xb2]);
+        crypt(&mut data, V1_KEY);
+        assert_eq!(&data, b"hello, world!");
+    }
+
+    #[test]
+    fn v1() {
+        let archive = pack(Version::V1);
+        check(&archive);
+        assert_eq!(archive, V1_FIXTURE);
+    }
+
+    #[test]
+    fn v3() {
+        check(&pack(Version::V3));
+        check(V3_FIXTURE);
+    }
+
+    #[test]
+    fn v1_fixture() {
+        check(V1_FIXTURE);
+    }
+
+    #[test]
+    fn truncated() {
+        for fixture in [V1_FIXTURE, V3_FIXTURE] {
+            assert!(Archive::open(Cursor::new(&fixture[..fixture.len() - 1])).is_err());
+        }
+        assert!(Archive::open(Cursor::new(&b"RGSSAD\0\x02"[..])).is_err());
+    }
+}
@@ -2,9 +2,13 @@ use clap::{CommandFactory, error::ErrorKind};
 use common::Format;
 use indicatif::ProgressStyle;
 use rayon::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
 
-use super::{Cli, ConvArgs};
+use super::{Cli, ConvArgs, manifest, report, walk};
+use report::Status;
 
+#[allow(clippy::too_many_lines)]
 pub fn convert(args: ConvArgs) {
     let ConvArgs {
         src,
@@ -15,6 +19,12 @@ pub fn convert(args: ConvArgs) {
         output_file_ext,
         single_thread,
         thread_count,
+        links,
+        recursive,
+        include,
+        exclude,
+        incremental,
+        report: report_path,
     } = args;
 
     if let Some(count) = thread_count {
@@ -52,30 +62,29 @@ pub fn convert(args: ConvArgs) {
     if let Err(e) = std::fs::create_dir(&dest)
         && e.kind() != std::io::ErrorKind::AlreadyExists
     {
-        eprintln!("failed to create destination directory: {e}");
-        return;
+        let error = format!("failed to create destination directory: {e}");
+        report::abort(error, report_path.as_deref());
     }
 
-    let read_dir = match std::fs::read_dir(&src) {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("failed to read {}: {e}", src.display());
-            return;
-        }
+    let filter = match walk::Filter::new(&include, &exclude) {
+        Ok(filter) => filter,
+        Err(e) => report::abort(format!("invalid glob: {e}"), report_path.as_deref()),
     };
-
-    let mut entries: Vec<_> = if fail_on_error {
-        match read_dir.collect() {
-            Ok(e) => e,
-            Err(e) => {
-                eprintln!("failed to read directory entry: {e}");
-                return;
-            }
-        }
-    } else {
-        read_dir.filter_map(Result::ok).collect()
+    let entries = match walk::entries(&src, recursive, &filter, fail_on_error) {
+        Ok(entries) => entries,
+        Err(e) => report::abort(e, report_path.as_deref()),
     };
-    entries.sort_by_key(std::fs::DirEntry::path);
+
+    let incremental = incremental.then(|| {
+        let settings = format!(
+            "convert {from:?} (.{}) to {to:?} (.{}), links: {links}",
+            input_file_ext.display(),
+            output_file_ext.display()
+        );
+        manifest::Incremental::load(&dest, settings)
+    });
+    let skipped = AtomicUsize::new(0);
+    let report = report::Report::default();
 
     let pb = indicatif::ProgressBar::new(entries.len() as _);
     pb.set_style(
@@ -87,28 +96,64 @@ pub fn convert(args: ConvArgs) {
     );
     pb.enable_steady_tick(std::time::Duration::from_millis(50));
 
-    let entry_fn = |entry: &std::fs::DirEntry| {
+    let entry_fn = |entry: &walk::Entry| {
         pb.inc(1);
-        let src_path = entry.path();
+        let started = Instant::now();
+        let src_path = &entry.path;
         // if not a file *or* the file extension does not match what it should, print warning and continue
-        if !entry.file_type().expect("couldn't get file type").is_file()
-            || src_path.extension().is_none_or(|ext| ext != input_file_ext)
-        {
+        if !entry.is_file || src_path.extension().is_none_or(|ext| ext != input_file_ext) {
             pb.println(format!("[WARN]: Ignoring {}", src_path.display()));
+            report.record(entry, Status::Skipped, started, None, None);
             return Some(());
         }
 
-        let filename = src_path.file_name().expect("entry should have a file name");
-        let dest_path = dest.join(filename).with_extension(output_file_ext);
-
-        if let Err(e) = convert_data::<common::Value>(&src_path, &dest_path, to, from) {
+        let output = entry.relative.with_extension(output_file_ext);
+        let dest_path = dest.join(&output);
+        let failed = |e: String| {
+            let output = Some((dest_path.as_path(), output.as_path()));
+            report.record(entry, Status::Error, started, output, Some(e.clone()));
             pb.println(e);
             if fail_on_error {
                 pb.abandon();
                 return None;
             }
+            Some(())
+        };
+
+        if let Some(parent) = dest_path.parent()
+            && let Err(e) = std::fs::create_dir_all(parent)
+        {
+            return failed(format!("failed to create {}: {e}", parent.display()));
         }
 
+        let mut hash = None;
+        if let Some(incremental) = &incremental {
+            match incremental.check(&entry.relative, src_path, &dest_path) {
+                Ok(manifest::Check::UpToDate) => {
+                    skipped.fetch_add(1, Ordering::Relaxed);
+                    let output = Some((dest_path.as_path(), output.as_path()));
+                    report.record(entry, Status::Skipped, started, output, None);
+                    return Some(());
+                }
+                Ok(manifest::Check::Changed(new_hash)) => hash = Some(new_hash),
+                Err(e) => return failed(e),
+            }
+        }
+
+        let result = if links {
+            convert_linked(src_path, &dest_path, to, from)
+        } else {
+            convert_data::<common::Value>(src_path, &dest_path, to, from)
+        };
+        if let Err(e) = result {
+            return failed(e);
+        }
+
+        if let (Some(incremental), Some(hash)) = (&incremental, hash) {
+            incremental.converted(&entry.relative, hash, &output);
+        }
+        let output = Some((dest_path.as_path(), output.as_path()));
+        report.record(entry, Status::Ok, started, output, None);
         Some(())
     };
 
@@ -121,6 +166,23 @@ pub fn convert(args: ConvArgs) {
     if result.is_some() {
         pb.finish();
     }
+
+    if let Some(incremental) = incremental {
+        let skipped = skipped.into_inner();
+        if skipped > 0 {
+            eprintln!("[INFO]: Skipped {skipped} unchanged file(s)");
+        }
+        match incremental.finish(&src, &dest) {
+            Ok(removed) => {
+                for path in removed {
+                    eprintln!("[INFO]: Removed {}", path.display());
+                }
+            }
+            Err(e) => eprintln!("failed to update manifest: {e}"),
+        }
+    }
+
+    report::finish(report, report_path.as_deref());
 }
 
 fn convert_data<T>(
@@ -151,3 +213,27 @@ where
 
     Ok(())
 }
+
+fn convert_linked(
+    src_path: &std::path::Path,
+    dest_path: &std::path::Path,
+    to: Format,
+    from: Format,
+) -> Result<(), String> {
+    let input = std::fs::File::open(src_path)
+        .map_err(|e| format!("couldn't open {}: {e}", src_path.display()))?;
+    let input = std::io::BufReader::new(input);
+
+    let value = common::conv_read_linked(from, input)
+        .map_err(|e| format!("failed to parse {}: {e}", src_path.display()))?;
+
+    let output = std::fs::File::create(dest_path)
+        .map_err(|e| format!("couldn't open {}: {e}", dest_path.display()))?;
+    let output = std::io::BufWriter::new(output);
+
+    let floats = common::links::FloatStyle::guess(dest_path);
+    common::conv_write_linked(&value, to, floats, output)
+        .map_err(|e| format!("failed to convert {}: {e}", src_path.display()))?;
+
+    Ok(())
+}
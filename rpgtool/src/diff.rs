@@ -0,0 +1,188 @@
+use common::Format;
+
+use crate::DiffArgs;
+use crate::structured::{self, DataVisitor, GameData};
+use crate::tree::{self, Path, Segment, Tree};
+
+pub fn diff(args: DiffArgs) {
+    let DiffArgs { a, b, game_version } = args;
+
+    // text files don't say which game they're from, so without a version they're compared loosely
+    let game_version = game_version.or_else(|| {
+        a.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(structured::detect::from_ext)
+    });
+
+    let name = a
+        .file_prefix()
+        .and_then(|prefix| prefix.to_str())
+        .unwrap_or_default();
+    let files = DiffFiles { a: &a, b: &b, name };
+    let result = game_version
+        .and_then(|game_version| structured::dispatch(game_version, name, files))
+        .unwrap_or_else(|| diff_files::<common::Value>(files));
+
+    match result {
+        Ok(changes) if changes.is_empty() => {}
+        Ok(changes) => {
+            for change in changes {
+                println!("{change}");
+            }
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct DiffFiles<'a> {
+    a: &'a std::path::Path,
+    b: &'a std::path::Path,
+    name: &'a str,
+}
+
+impl DataVisitor for DiffFiles<'_> {
+    type Output = Result<Vec<String>, String>;
+
+    fn visit<T: GameData>(self) -> Self::Output {
+        diff_files::<T>(self)
+    }
+}
+
+fn diff_files<T: GameData>(files: DiffFiles<'_>) -> Result<Vec<String>, String> {
+    let read = |path: &std::path::Path| {
+        let format = Format::guess(path).unwrap_or(Format::Marshal);
+        let value =
+            tree::read::<T>(path, format)?.ok_or_else(|| format!("{} is empty", path.display()))?;
+        tree::to_tree(value).map_err(|e| format!("failed to convert {}: {e}", path.display()))
+    };
+    let (a, b) = (read(files.a)?, read(files.b)?);
+
+    let mut changes = vec![];
+    diff_values(&Path::new(files.name), &a, &b, &mut changes);
+    Ok(changes)
+}
+
+/// Map events have a position, which is shown as a move instead of two field changes.
+fn position(value: &Tree) -> Option<(i64, i64)> {
+    let object = value.as_object()?;
+    if !object.contains_key("pages") {
+        return None;
+    }
+    let x = object.get("x")?.as_i64()?;
+    let y = object.get("y")?.as_i64()?;
+    Some((x, y))
+}
+
+fn diff_values(path: &Path, a: &Tree, b: &Tree, changes: &mut Vec<String>) {
+    if a == b {
+        return;
+    }
+
+    match (a, b) {
+        (Tree::Object(x), Tree::Object(y)) => {
+            if let (Some(a_dims), Some(b_dims)) = (tree::table_dims(a), tree::table_dims(b)) {
+                if a_dims == b_dims {
+                    let (Some(Tree::Array(x)), Some(Tree::Array(y))) =
+                        (x.get("data"), y.get("data"))
+                    else {
+                        return;
+                    };
+                    for (index, (a, b)) in x.iter().zip(y).enumerate() {
+                        let path = path.join(Segment::Cell(tree::cell(&a_dims, index)));
+                        diff_values(&path, a, b, changes);
+                    }
+                } else {
+                    changes.push(format!(
+                        "{path}: resized {} -> {}",
+                        dims(&a_dims),
+                        dims(&b_dims)
+                    ));
+                }
+                return;
+            }
+
+            let positions = position(a).zip(position(b));
+            if let Some(((ax, ay), (bx, by))) = positions
+                && (ax, ay) != (bx, by)
+            {
+                changes.push(format!("{path}: moved ({ax}, {ay}) -> ({bx}, {by})"));
+            }
+
+            for key in tree::sorted_keys(x.keys().chain(y.keys())) {
+                if positions.is_some() && (key == "x" || key == "y") {
+                    continue;
+                }
+                let path = path.join(Segment::Field(key.clone()));
+                diff_entry(&path, x.get(key), y.get(key), changes);
+            }
+        }
+        (Tree::Array(x), Tree::Array(y)) => diff_arrays(path, x, y, changes),
+        _ => changes.push(format!(
+            "{path}: {} -> {}",
+            tree::describe(Some(a)),
+            tree::describe(Some(b))
+        )),
+    }
+}
+
+/// Like [`diff_values`], but either side may be missing.
+fn diff_entry(path: &Path, a: Option<&Tree>, b: Option<&Tree>, changes: &mut Vec<String>) {
+    match (a, b) {
+        (Some(a), Some(b)) => diff_values(path, a, b, changes),
+        (Some(a), None) => changes.push(format!("{path}: removed {}", tree::describe(Some(a)))),
+        (None, Some(b)) => changes.push(format!("{path}: added {}", tree::describe(Some(b)))),
+        (None, None) => {}
+    }
+}
+
+/// Database arrays are compared by id. Other arrays are compared element by element, after
+/// skipping anything the same at the start and end (so one inserted command doesn't change every
+/// command after it).
+fn diff_arrays(path: &Path, a: &[Tree], b: &[Tree], changes: &mut Vec<String>) {
+    if let Some((x, y)) = tree::entries(a).zip(tree::entries(b)) {
+        let mut ids: Vec<_> = x.keys().chain(y.keys()).copied().collect();
+        ids.sort_unstable();
+        ids.dedup();
+        for id in ids {
+            let path = path.join(Segment::Id(id));
+            diff_entry(&path, x.get(&id).copied(), y.get(&id).copied(), changes);
+        }
+        return;
+    }
+
+    let prefix = a.iter().zip(b).take_while(|(a, b)| a == b).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (x, y) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    if x.len() == y.len() {
+        for (index, (a, b)) in x.iter().zip(y).enumerate() {
+            diff_values(&path.join(Segment::Index(prefix + index)), a, b, changes);
+        }
+        return;
+    }
+
+    // indices are where the element is in the file it's from
+    for (index, a) in x.iter().enumerate() {
+        let path = path.join(Segment::Index(prefix + index));
+        diff_entry(&path, Some(a), None, changes);
+    }
+    for (index, b) in y.iter().enumerate() {
+        let path = path.join(Segment::Index(prefix + index));
+        diff_entry(&path, None, Some(b), changes);
+    }
+}
+
+fn dims(dims: &[usize]) -> String {
+    let dims: Vec<_> = dims.iter().map(ToString::to_string).collect();
+    dims.join("x")
+}
@@ -0,0 +1,265 @@
+use alox_48::Value;
+use clap::{CommandFactory, error::ErrorKind};
+use common::Format;
+
+use crate::structured::{self, EventCommand};
+use crate::{Cli, EventsArgs, EventsCommand, EventsExportArgs, EventsImportArgs, GameVer};
+
+mod script;
+
+/// Where a list of event commands lives in a data file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    CommonEvent {
+        id: i32,
+    },
+    /// Pages count from 1, like in the editor.
+    Event {
+        id: i32,
+        page: usize,
+    },
+    Troop {
+        id: i32,
+        page: usize,
+    },
+}
+
+impl std::fmt::Display for Section {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Section::CommonEvent { id } => write!(f, "common_event {id}"),
+            Section::Event { id, page } => write!(f, "event {id} page {page}"),
+            Section::Troop { id, page } => write!(f, "troop {id} page {page}"),
+        }
+    }
+}
+
+impl std::str::FromStr for Section {
+    type Err = String;
+
+    // Anything after the section itself (like the name) is ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let kind = words.next().ok_or("expected a section kind")?;
+        let id = words
+            .next()
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| format!("expected an id after {kind}"))?;
+
+        let mut page = || {
+            words
+                .next()
+                .filter(|&word| word == "page")
+                .and_then(|_| words.next())
+                .and_then(|page| page.parse().ok())
+                .filter(|&page| page > 0)
+                .ok_or_else(|| format!("expected a page number after {kind} {id}"))
+        };
+
+        match kind {
+            "common_event" => Ok(Section::CommonEvent { id }),
+            "event" => Ok(Section::Event { id, page: page()? }),
+            "troop" => Ok(Section::Troop { id, page: page()? }),
+            _ => Err(format!("unknown section kind {kind}")),
+        }
+    }
+}
+
+pub fn events(args: EventsArgs) {
+    let result = match args.command {
+        EventsCommand::Export(args) => export(args),
+        EventsCommand::Import(args) => import(args),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+fn resolve_game_version(game_version: Option<GameVer>, data: &std::path::Path) -> GameVer {
+    let detected = data
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(structured::detect::from_ext);
+    let Some(game_version) = game_version.or(detected) else {
+        let mut command = Cli::command();
+        command
+            .error(
+                ErrorKind::DisplayHelp,
+                "unable to determine game version, please specify it",
+            )
+            .exit()
+    };
+    game_version
+}
+
+fn read_data(path: &std::path::Path) -> Result<Value, String> {
+    let format = Format::guess(path).unwrap_or(Format::Marshal);
+    let input =
+        std::fs::File::open(path).map_err(|e| format!("couldn't open {}: {e}", path.display()))?;
+    let input = std::io::BufReader::new(input);
+    let value: common::Value = common::conv_read(format, input)
+        .map_err(|e| format!("failed to parse {}: {e}", path.display()))?;
+    Ok(value.0)
+}
+
+fn export(args: EventsExportArgs) -> Result<(), String> {
+    let EventsExportArgs {
+        data,
+        script,
+        game_version,
+    } = args;
+    let game_version = resolve_game_version(game_version, &data);
+
+    // move routes are much easier to read typed, and the script format is XP only anyway
+    structured::set_typed_move_commands(matches!(game_version, GameVer::RPGXP));
+
+    let mut value = read_data(&data)?;
+    let mut out = String::new();
+    for_each_list(&mut value, |section, name, list| {
+        let list: Vec<EventCommand> =
+            alox_48::from_value(list).map_err(|e| format!("failed to parse {section}: {e}"))?;
+        script::write_section(&mut out, section, name, &list, game_version)
+            .map_err(|e| format!("failed to write {section}: {e}"))
+    })?;
+
+    std::fs::write(&script, out).map_err(|e| format!("couldn't write {}: {e}", script.display()))
+}
+
+fn import(args: EventsImportArgs) -> Result<(), String> {
+    let EventsImportArgs {
+        script,
+        data,
+        game_version,
+    } = args;
+    let game_version = resolve_game_version(game_version, &data);
+    structured::set_encoded_string_parameters(matches!(game_version, GameVer::RPGVXAce));
+
+    let text = std::fs::read_to_string(&script)
+        .map_err(|e| format!("couldn't open {}: {e}", script.display()))?;
+    let mut sections = script::parse(&text, game_version)
+        .map_err(|e| format!("failed to parse {}: {e}", script.display()))?;
+
+    let mut value = read_data(&data)?;
+    for_each_list(&mut value, |section, _, list| {
+        let Some(index) = sections.iter().position(|(s, _)| *s == section) else {
+            return Ok(());
+        };
+        let (_, commands) = sections.remove(index);
+        *list =
+            alox_48::to_value(commands).map_err(|e| format!("failed to convert {section}: {e}"))?;
+        Ok(())
+    })?;
+
+    // anything left over didn't match a list in the data file, which is almost certainly a mistake
+    if let Some((section, _)) = sections.first() {
+        return Err(format!("{section} does not exist in {}", data.display()));
+    }
+
+    let format = Format::guess(&data).unwrap_or(Format::Marshal);
+    let output = std::fs::File::create(&data)
+        .map_err(|e| format!("couldn't open {}: {e}", data.display()))?;
+    let output = std::io::BufWriter::new(output);
+    common::conv_write(common::Value(value), format, output)
+        .map_err(|e| format!("failed to write {}: {e}", data.display()))
+}
+
+fn class(value: &Value) -> Option<&str> {
+    match value {
+        Value::Object(object) => Some(object.class.as_str()),
+        _ => None,
+    }
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(object) => object.fields.get(name),
+        _ => None,
+    }
+}
+
+fn field_mut<'a>(value: &'a mut Value, name: &str) -> Option<&'a mut Value> {
+    match value {
+        Value::Object(object) => object.fields.get_mut(name),
+        _ => None,
+    }
+}
+
+fn id(value: &Value) -> i32 {
+    field(value, "@id")
+        .and_then(Value::as_integer)
+        .copied()
+        .unwrap_or_default()
+}
+
+fn name(value: &Value) -> String {
+    let name = match field(value, "@name") {
+        Some(Value::Instance(instance)) => &*instance.value,
+        Some(name) => name,
+        None => return String::new(),
+    };
+    match name {
+        Value::String(string) => string.to_string_lossy().into_owned(),
+        _ => String::new(),
+    }
+}
+
+/// Calls `f` with every list of event commands in map, common event, or troop data.
+fn for_each_list(
+    value: &mut Value,
+    mut f: impl FnMut(Section, &str, &mut Value) -> Result<(), String>,
+) -> Result<(), String> {
+    match value {
+        // RPG::Map
+        Value::Object(_) if class(value) == Some("RPG::Map") => {
+            let Some(Value::Hash(events)) = field_mut(value, "@events") else {
+                return Err("map has no events".to_string());
+            };
+            let mut events: Vec<_> = events.values_mut().collect();
+            events.sort_by_key(|event| id(event));
+            for event in events {
+                let (id, name) = (id(event), name(event));
+                for_each_page(event, |page, list| {
+                    f(Section::Event { id, page }, &name, list)
+                })?;
+            }
+        }
+        // Troops and CommonEvents
+        Value::Array(items) => {
+            for item in items {
+                let (id, name) = (id(item), name(item));
+                match class(item) {
+                    Some("RPG::CommonEvent") => {
+                        if let Some(list) = field_mut(item, "@list") {
+                            f(Section::CommonEvent { id }, &name, list)?;
+                        }
+                    }
+                    Some("RPG::Troop") => {
+                        for_each_page(item, |page, list| {
+                            f(Section::Troop { id, page }, &name, list)
+                        })?;
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ => return Err("this data file has no event commands".to_string()),
+    }
+    Ok(())
+}
+
+fn for_each_page(
+    value: &mut Value,
+    mut f: impl FnMut(usize, &mut Value) -> Result<(), String>,
+) -> Result<(), String> {
+    let Some(Value::Array(pages)) = field_mut(value, "@pages") else {
+        return Ok(());
+    };
+    for (index, page) in pages.iter_mut().enumerate() {
+        if let Some(list) = field_mut(page, "@list") {
+            f(index + 1, list)?;
+        }
+    }
+    Ok(())
+}
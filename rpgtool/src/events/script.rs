@@ -0,0 +1,427 @@
+//! The event script format.
+//!
+//! Each list starts with a header line like `== event 1 page 1 (EV001)`, and has one command per line
+//! indented two spaces per [`EventCommand::indent`]. Commands are written in RON, like
+//! `ShowChoices(choices: ["Yes", "No"], cancel_type: 2)`.
+//!
+//! Commands that continue the one before them (like the extra lines of Show Text) are written as
+//! `| more text` instead. The continuation code is figured out from the command they continue.
+//!
+//! Blank lines and lines starting with `#` are ignored.
+
+use std::fmt::Write;
+
+use crate::GameVer;
+use crate::structured::{
+    EventCommand, MoveCommand, ParameterType, TypedEventCommand, TypedMoveCommand,
+};
+
+use super::Section;
+
+/// The code of the command that continues `code`, if any.
+fn continuation(code: u16, game_version: GameVer) -> Option<u16> {
+    let code = match (code, game_version) {
+        (101, _) => 401,
+        (108, _) => 408,
+        (355, _) => 655,
+        (209, GameVer::RPGXP) => 509,
+        (205, GameVer::RPGVX | GameVer::RPGVXAce) => 505,
+        (105, GameVer::RPGVXAce) => 405,
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn is_move_continuation(code: u16) -> bool {
+    matches!(code, 505 | 509)
+}
+
+fn ron_config() -> ron::ser::PrettyConfig {
+    ron::ser::PrettyConfig::new()
+        .compact_arrays(true)
+        .compact_structs(true)
+        .compact_maps(true)
+}
+
+fn to_ron<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    ron::ser::to_string_pretty(value, ron_config()).map_err(|e| e.to_string())
+}
+
+/// Writes a list of commands under a header.
+pub fn write_section(
+    out: &mut String,
+    section: Section,
+    name: &str,
+    list: &[EventCommand],
+    game_version: GameVer,
+) -> Result<(), String> {
+    // the typed commands are RGSS1's, later versions are written raw
+    let typed = matches!(game_version, GameVer::RPGXP);
+
+    if name.is_empty() {
+        let _ = writeln!(out, "== {section}");
+    } else {
+        let _ = writeln!(out, "== {section} ({name})");
+    }
+
+    let mut continued = None;
+    for command in list {
+        let indent = "  ".repeat(command.indent);
+
+        if continued == Some(command.code) {
+            match command.parameters.as_slice() {
+                [ParameterType::String(text)]
+                    if !is_move_continuation(command.code) && !text.contains(['\n', '\r']) =>
+                {
+                    let _ = writeln!(out, "{indent}| {text}");
+                    continue;
+                }
+                [ParameterType::MoveCommand(move_command)]
+                    if is_move_continuation(command.code) =>
+                {
+                    let move_command = if typed {
+                        TypedMoveCommand::from_raw(move_command.code, &move_command.parameters)
+                    } else {
+                        TypedMoveCommand::Raw {
+                            code: move_command.code,
+                            parameters: move_command.parameters.clone(),
+                        }
+                    };
+                    let _ = writeln!(out, "{indent}| {}", to_ron(&move_command)?);
+                    continue;
+                }
+                _ => {}
+            }
+        }
+
+        let typed_command = if typed {
+            TypedEventCommand::from_raw(command.code, &command.parameters)
+        } else {
+            TypedEventCommand::Raw {
+                code: command.code,
+                parameters: command.parameters.clone(),
+            }
+        };
+        let _ = writeln!(out, "{indent}{}", to_ron(&typed_command)?);
+        continued = continuation(command.code, game_version);
+    }
+
+    let _ = writeln!(out);
+    Ok(())
+}
+
+/// Parses a script into its sections.
+pub fn parse(
+    script: &str,
+    game_version: GameVer,
+) -> Result<Vec<(Section, Vec<EventCommand>)>, String> {
+    let mut sections: Vec<(Section, Vec<EventCommand>)> = vec![];
+    let mut continued = None;
+
+    for (number, line) in script.lines().enumerate() {
+        let error = |message: String| format!("line {}: {message}", number + 1);
+
+        let content = line.trim_start_matches(' ');
+        if content.is_empty() || content.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = content.strip_prefix("==") {
+            let section = header.parse().map_err(error)?;
+            sections.push((section, vec![]));
+            continued = None;
+            continue;
+        }
+
+        let Some((_, list)) = sections.last_mut() else {
+            return Err(error(
+                "expected a section header before any commands".to_string(),
+            ));
+        };
+
+        let spaces = line.len() - content.len();
+        if spaces % 2 != 0 {
+            return Err(error(
+                "indentation should be a multiple of two spaces".to_string(),
+            ));
+        }
+        let indent = spaces / 2;
+
+        if let Some(rest) = content.strip_prefix('|') {
+            let Some(code) = continued else {
+                return Err(error("this command doesn't continue anything".to_string()));
+            };
+            // the space after the bar is only there for readability
+            let rest = rest.strip_prefix(' ').unwrap_or(rest);
+
+            let parameter = if is_move_continuation(code) {
+                let move_command: TypedMoveCommand =
+                    ron::from_str(rest).map_err(|e| error(e.to_string()))?;
+                let (code, parameters) = move_command.to_raw();
+                ParameterType::MoveCommand(MoveCommand { code, parameters })
+            } else {
+                ParameterType::String(rest.to_string())
+            };
+            list.push(EventCommand {
+                code,
+                indent,
+                parameters: vec![parameter],
+            });
+            continue;
+        }
+
+        let command: TypedEventCommand =
+            ron::from_str(content).map_err(|e| error(e.to_string()))?;
+        let (code, parameters) = command.to_raw();
+        list.push(EventCommand {
+            code,
+            indent,
+            parameters,
+        });
+        continued = continuation(code, game_version);
+    }
+
+    Ok(sections)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structured::MoveRoute;
+
+    fn command(code: u16, indent: usize, parameters: Vec<ParameterType>) -> EventCommand {
+        EventCommand {
+            code,
+            indent,
+            parameters,
+        }
+    }
+
+    fn text(text: &str) -> ParameterType {
+        ParameterType::String(text.to_string())
+    }
+
+    fn move_command(code: u16) -> ParameterType {
+        ParameterType::MoveCommand(MoveCommand {
+            code,
+            parameters: vec![],
+        })
+    }
+
+    fn raw(list: &[EventCommand]) -> Vec<(u16, usize, Vec<ParameterType>)> {
+        list.iter()
+            .map(|command| (command.code, command.indent, command.parameters.clone()))
+            .collect()
+    }
+
+    fn write(list: &[EventCommand], game_version: GameVer) -> String {
+        let mut out = String::new();
+        write_section(
+            &mut out,
+            Section::CommonEvent { id: 1 },
+            "",
+            list,
+            game_version,
+        )
+        .expect("list can be written");
+        out
+    }
+
+    fn round_trip(list: &[EventCommand], game_version: GameVer) -> String {
+        let script = write(list, game_version);
+        let sections = parse(&script, game_version).expect("script can be parsed");
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].0, Section::CommonEvent { id: 1 });
+        assert_eq!(raw(&sections[0].1), raw(list));
+        script
+    }
+
+    #[test]
+    fn headers() {
+        let mut out = String::new();
+        let section = Section::Event { id: 3, page: 2 };
+        write_section(&mut out, section, "EV003", &[], GameVer::RPGXP).expect("empty list");
+        write_section(&mut out, section, "", &[], GameVer::RPGXP).expect("empty list");
+        assert_eq!(out, "== event 3 page 2 (EV003)\n\n== event 3 page 2\n\n");
+
+        let script = "== common_event 1 (Name)\n== event 2 page 1\n== troop 3 page 4\n";
+        let sections: Vec<_> = parse(script, GameVer::RPGXP)
+            .expect("headers are valid")
+            .into_iter()
+            .map(|(section, list)| {
+                assert!(list.is_empty());
+                section
+            })
+            .collect();
+        assert_eq!(
+            sections,
+            [
+                Section::CommonEvent { id: 1 },
+                Section::Event { id: 2, page: 1 },
+                Section::Troop { id: 3, page: 4 },
+            ]
+        );
+    }
+
+    #[test]
+    fn indentation() {
+        let script = "== common_event 1\nEmpty()\n  Empty()\n    Empty()\nEmpty()\n";
+        let sections = parse(script, GameVer::RPGXP).expect("script is valid");
+        let indents: Vec<_> = sections[0].1.iter().map(|command| command.indent).collect();
+        assert_eq!(indents, [0, 1, 2, 0]);
+
+        let list = [
+            command(111, 0, vec![ParameterType::Integer(0)]),
+            command(355, 1, vec![text("x = 1")]),
+            command(655, 1, vec![text("y = 2")]),
+            command(0, 1, vec![]),
+            command(412, 0, vec![]),
+        ];
+        let script = round_trip(&list, GameVer::RPGVX);
+        assert!(
+            script.contains("\n  Raw(code: 355, parameters: [String(\"x = 1\")])\n  | y = 2\n")
+        );
+    }
+
+    #[test]
+    fn continuations() {
+        let cases = [
+            (GameVer::RPGXP, [101, 108, 355, 209], [105, 205]),
+            (GameVer::RPGVX, [101, 108, 355, 205], [105, 209]),
+            (GameVer::RPGVXAce, [101, 108, 355, 205], [209, 111]),
+        ];
+        for (game_version, continued, not_continued) in cases {
+            for code in continued {
+                let next = continuation(code, game_version).expect("command is continued");
+                let parameter = if is_move_continuation(next) {
+                    move_command(1)
+                } else {
+                    text("more")
+                };
+                let list = [command(code, 0, vec![]), command(next, 0, vec![parameter])];
+                let script = round_trip(&list, game_version);
+                assert_eq!(script.lines().nth(2).map(|line| &line[..2]), Some("| "));
+            }
+            for code in not_continued {
+                assert_eq!(continuation(code, game_version), None);
+            }
+        }
+        assert_eq!(continuation(105, GameVer::RPGVXAce), Some(405));
+
+        // a continuation that can't be written as one is written as its own command, and so is the
+        // rest of the block
+        let list = [
+            command(101, 0, vec![text("first")]),
+            command(401, 0, vec![text("two\nlines")]),
+            command(401, 0, vec![text("after")]),
+        ];
+        let script = round_trip(&list, GameVer::RPGXP);
+        assert!(script.contains(
+            "ShowTextContinued(text: \"two\\nlines\")\nShowTextContinued(text: \"after\")\n"
+        ));
+    }
+
+    #[test]
+    fn round_trips() {
+        let xp = [
+            command(101, 0, vec![text("Hello")]),
+            command(401, 0, vec![text("world")]),
+            command(
+                102,
+                0,
+                vec![
+                    ParameterType::Array(vec![text("Yes"), text("No")]),
+                    ParameterType::Integer(2),
+                ],
+            ),
+            command(402, 0, vec![ParameterType::Integer(0), text("Yes")]),
+            command(
+                209,
+                1,
+                vec![
+                    ParameterType::Integer(-1),
+                    ParameterType::MoveRoute(MoveRoute::default()),
+                ],
+            ),
+            command(509, 1, vec![move_command(1)]),
+            command(509, 1, vec![move_command(0)]),
+            command(0, 1, vec![]),
+            command(404, 0, vec![]),
+            command(0, 0, vec![]),
+        ];
+        round_trip(&xp, GameVer::RPGXP);
+
+        let vx = [
+            command(101, 0, vec![text("Hello")]),
+            command(401, 0, vec![text("")]),
+            command(
+                205,
+                0,
+                vec![
+                    ParameterType::Integer(0),
+                    ParameterType::MoveRoute(MoveRoute::default()),
+                ],
+            ),
+            command(505, 0, vec![move_command(4)]),
+            command(0, 0, vec![]),
+        ];
+        round_trip(&vx, GameVer::RPGVX);
+
+        let ace = [
+            command(
+                105,
+                0,
+                vec![ParameterType::Integer(2), ParameterType::Bool(false)],
+            ),
+            command(405, 0, vec![text("Scrolling")]),
+            command(405, 0, vec![text("text")]),
+            command(108, 0, vec![text("note")]),
+            command(408, 0, vec![text("more")]),
+            command(0, 0, vec![]),
+        ];
+        round_trip(&ace, GameVer::RPGVXAce);
+    }
+
+    #[test]
+    fn errors() {
+        let cases = [
+            (
+                "Empty()\n",
+                "line 1: expected a section header before any commands",
+            ),
+            (
+                "== event 1\n",
+                "line 1: expected a page number after event 1",
+            ),
+            ("== map 1\n", "line 1: unknown section kind map"),
+            (
+                "== common_event 1\n   Empty()\n",
+                "line 2: indentation should be a multiple of two spaces",
+            ),
+            (
+                "== common_event 1\nEmpty()\n| text\n",
+                "line 3: this command doesn't continue anything",
+            ),
+        ];
+        for (script, message) in cases {
+            assert_eq!(
+                parse(script, GameVer::RPGXP).err().as_deref(),
+                Some(message)
+            );
+        }
+        // 105 only has a continuation in Ace
+        let script = "== common_event 1\nRaw(code: 105, parameters: [])\n| text\n";
+        assert!(parse(script, GameVer::RPGVXAce).is_ok());
+        assert_eq!(
+            parse(script, GameVer::RPGVX).err().as_deref(),
+            Some("line 3: this command doesn't continue anything")
+        );
+    }
+
+    #[test]
+    fn malformed_line() {
+        let script = "# comment\n== common_event 1\n\nShowText(text: \"hi\")\n  Wait(frames: )\n";
+        let error = parse(script, GameVer::RPGXP).expect_err("line 5 is malformed");
+        assert!(error.starts_with("line 5: "), "{error}");
+    }
+}
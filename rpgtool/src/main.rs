@@ -6,10 +6,22 @@ use clap::{CommandFactory, Parser};
 use common::Format;
 use std::path::PathBuf;
 
+mod archive;
 mod conv;
+mod diff;
+mod events;
+mod manifest;
+mod merge;
 mod pack;
+mod report;
+mod save;
 mod structured;
+mod sync;
+mod textconv;
+mod tree;
 mod unpack;
+mod verify;
+mod walk;
 
 /// Utility for working with RPG Maker XP - VX Ace projects.
 #[derive(Parser)]
@@ -33,6 +45,47 @@ enum Commands {
     ///
     /// Unknown game files are converted using the normal structured format.
     Structured(StructuredArgs),
+    /// Check that every file in a project survives a round trip through a text format.
+    ///
+    /// Both the loose and structured representations are checked, and any file that doesn't
+    /// convert back to the exact same bytes is reported.
+    Verify(VerifyArgs),
+    /// Edit event commands as text.
+    ///
+    /// Exports the event command lists of a map, common events, or troops file to a script with
+    /// one command per line, and imports them back.
+    Events(EventsArgs),
+    /// Merge three versions of a data file, for use as a git merge driver.
+    ///
+    /// Changes are merged by database id, event id, and map tile, so both sides can edit the same
+    /// file as long as they don't edit the same thing. The result is written to the file with our
+    /// version, and anything both sides changed differently is reported as a conflict.
+    MergeDriver(MergeDriverArgs),
+    /// Show what changed between two versions of a data file.
+    ///
+    /// Changes are listed by where they are, like `Actors[3].name: "Aluxes" -> "Alex"`.
+    /// Exits with 1 if the files differ, like diff.
+    Diff(DiffArgs),
+    /// Print a data file as text, for use as a git textconv filter.
+    ///
+    /// Recognized game files are written using the structured representation.
+    Textconv(TextconvArgs),
+    /// Keep a data directory and a text copy of it in sync.
+    ///
+    /// Files are converted with the structured representation, in whichever direction changed
+    /// since the last sync. Files that changed on both sides are reported instead of overwritten.
+    ///
+    /// Deletions aren't synced. A file deleted on one side is converted back from the other.
+    Sync(SyncArgs),
+    /// Work with the encrypted archives games are shipped in.
+    ///
+    /// Supports `Game.rgssad` (XP), `Game.rgss2a` (VX), and `Game.rgss3a` (VX Ace).
+    Archive(ArchiveArgs),
+    /// Convert save files to text and back.
+    ///
+    /// Saves are several marshal values written one after another, which are named after what
+    /// the game saved in them, like `game_party` and `game_map`.
+    Save(SaveArgs),
     /// Generate completions for the specified shell.
     Completions(CompletionArgs),
 }
@@ -43,6 +96,7 @@ struct CompletionArgs {
 }
 
 #[derive(clap::Args)]
+#[allow(clippy::struct_excessive_bools)]
 struct ConvArgs {
     /// The source directory.
     #[arg(value_hint = clap_complete::ValueHint::DirPath)]
@@ -75,9 +129,37 @@ struct ConvArgs {
     single_thread: bool,
     #[arg(long = "thread-count", conflicts_with = "single_thread")]
     thread_count: Option<usize>,
+    /// Preserve object links (shared objects) as `$id`/`$ref` pairs.
+    ///
+    /// Without this, shared objects are duplicated when converting from marshal.
+    #[arg(long)]
+    links: bool,
+    /// Convert files in subdirectories too, mirroring them in the destination.
+    #[arg(long, short = 'r')]
+    recursive: bool,
+    /// Only convert files matching this glob, relative to the source directory.
+    ///
+    /// Can be specified multiple times.
+    #[arg(long)]
+    include: Vec<String>,
+    /// Don't convert files matching this glob, relative to the source directory.
+    ///
+    /// Can be specified multiple times.
+    #[arg(long)]
+    exclude: Vec<String>,
+    /// Only convert files that changed since the last conversion.
+    ///
+    /// Keeps a manifest of source hashes in the destination directory, and removes outputs whose
+    /// source was deleted.
+    #[arg(long)]
+    incremental: bool,
+    /// Write a JSON summary of every file's status to this file.
+    #[arg(long, value_hint = clap_complete::ValueHint::FilePath)]
+    report: Option<PathBuf>,
 }
 
 #[derive(clap::Args)]
+#[allow(clippy::struct_excessive_bools)]
 struct StructuredArgs {
     /// The source directory.
     #[arg(value_hint = clap_complete::ValueHint::DirPath)]
@@ -86,7 +168,9 @@ struct StructuredArgs {
     #[arg(value_hint = clap_complete::ValueHint::DirPath)]
     dest: PathBuf,
     /// The game version to use.
-    game_version: GameVer,
+    ///
+    /// Detected from the project if not specified.
+    game_version: Option<GameVer>,
     /// The formats to convert from/to.
     ///
     /// Input comes first.
@@ -112,12 +196,346 @@ struct StructuredArgs {
     single_thread: bool,
     #[arg(long = "thread-count", conflicts_with = "single_thread")]
     thread_count: Option<usize>,
+    /// Write event commands with named fields instead of a code and parameter array.
+    ///
+    /// Commands that can't be represented this way are left as they are.
+    /// Only supported for RPG Maker XP.
+    #[arg(long = "typed-commands")]
+    typed_commands: bool,
+    /// Write move route commands with named fields, like `--typed-commands`.
+    ///
+    /// Only supported for RPG Maker XP.
+    #[arg(long = "typed-move-commands")]
+    typed_move_commands: bool,
+    /// Convert files in subdirectories too, mirroring them in the destination.
+    #[arg(long, short = 'r')]
+    recursive: bool,
+    /// Only convert files matching this glob, relative to the source directory.
+    ///
+    /// Can be specified multiple times.
+    #[arg(long)]
+    include: Vec<String>,
+    /// Don't convert files matching this glob, relative to the source directory.
+    ///
+    /// Can be specified multiple times.
+    #[arg(long)]
+    exclude: Vec<String>,
+    /// Only convert files that changed since the last conversion.
+    ///
+    /// Keeps a manifest of source hashes in the destination directory, and removes outputs whose
+    /// source was deleted.
+    #[arg(long)]
+    incremental: bool,
+    /// Write a JSON summary of every file's status to this file.
+    #[arg(long, value_hint = clap_complete::ValueHint::FilePath)]
+    report: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+#[allow(clippy::struct_excessive_bools)]
+struct VerifyArgs {
+    /// The source directory.
+    #[arg(value_hint = clap_complete::ValueHint::DirPath)]
+    src: PathBuf,
+    /// The game version to use.
+    ///
+    /// Detected from the project if not specified.
+    game_version: Option<GameVer>,
+    /// The format to round trip through.
+    #[arg(long, visible_short_alias = 'f', default_value = "json")]
+    format: Format,
+    /// Preserve object links in the loose representation, like `convert --links`.
+    #[arg(long)]
+    links: bool,
+    /// Exit on the first failure instead of checking every file.
+    #[arg(long = "fail-fast")]
+    fail_on_error: bool,
+    /// Check files in subdirectories too.
+    #[arg(long, short = 'r')]
+    recursive: bool,
+    /// Only check files matching this glob, relative to the source directory.
+    ///
+    /// Can be specified multiple times.
+    #[arg(long)]
+    include: Vec<String>,
+    /// Don't check files matching this glob, relative to the source directory.
+    ///
+    /// Can be specified multiple times.
+    #[arg(long)]
+    exclude: Vec<String>,
+    /// Run with a single thread instead of multiple threads.
+    #[arg(long = "single-thread")]
+    single_thread: bool,
+    #[arg(long = "thread-count", conflicts_with = "single_thread")]
+    thread_count: Option<usize>,
+}
+
+#[derive(clap::Args)]
+struct EventsArgs {
+    #[command(subcommand)]
+    command: EventsCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum EventsCommand {
+    /// Write the event commands in a data file to a script.
+    Export(EventsExportArgs),
+    /// Replace the event commands in a data file with the ones in a script.
+    Import(EventsImportArgs),
+}
+
+#[derive(clap::Args)]
+struct EventsExportArgs {
+    /// The map, common events, or troops file.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    data: PathBuf,
+    /// The script file.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    script: PathBuf,
+    /// The game version to use.
+    ///
+    /// Detected from the file extension if not specified.
+    game_version: Option<GameVer>,
+}
+
+#[derive(clap::Args)]
+struct EventsImportArgs {
+    /// The script file.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    script: PathBuf,
+    /// The map, common events, or troops file. It is modified in place.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    data: PathBuf,
+    /// The game version to use.
+    ///
+    /// Detected from the file extension if not specified.
+    game_version: Option<GameVer>,
+}
+
+#[derive(clap::Args)]
+struct MergeDriverArgs {
+    /// The common ancestor's version (%O).
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    base: PathBuf,
+    /// Our version (%A). The merge result is written here.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    ours: PathBuf,
+    /// Their version (%B).
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    theirs: PathBuf,
+    /// The path of the file being merged (%P).
+    ///
+    /// Used to recognize the file, as the others are temporary files.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    path: Option<PathBuf>,
+    /// The game version to use.
+    ///
+    /// Detected from the file extension if not specified.
+    #[arg(long)]
+    game_version: Option<GameVer>,
+}
+
+#[derive(clap::Args)]
+struct DiffArgs {
+    /// The old version of the file.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    a: PathBuf,
+    /// The new version of the file.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    b: PathBuf,
+    /// The game version to use.
+    ///
+    /// Detected from the file extension if not specified. Files from an unknown game version are
+    /// compared without the structured types.
+    #[arg(long)]
+    game_version: Option<GameVer>,
+}
+
+#[derive(clap::Args)]
+struct TextconvArgs {
+    /// The marshal file to print.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    file: PathBuf,
+    /// The game version to use.
+    ///
+    /// Detected from the file extension if not specified.
+    #[arg(long)]
+    game_version: Option<GameVer>,
+    /// The format to print.
+    #[arg(long, visible_short_alias = 'f', default_value = "json")]
+    format: Format,
+}
+
+#[derive(clap::Args)]
+struct SyncArgs {
+    /// The data directory.
+    #[arg(value_hint = clap_complete::ValueHint::DirPath)]
+    data: PathBuf,
+    /// The text directory.
+    #[arg(value_hint = clap_complete::ValueHint::DirPath)]
+    text: PathBuf,
+    /// The game version to use.
+    ///
+    /// Detected from the project if not specified.
+    game_version: Option<GameVer>,
+    /// The format of the text directory.
+    #[arg(long, visible_short_alias = 'f', default_value = "json")]
+    format: Format,
+    /// Keep watching both directories, converting files as they change.
+    #[arg(long, short = 'w')]
+    watch: bool,
+    /// Which side wins for files that changed on both sides since the last sync.
+    ///
+    /// By default these are left alone and reported.
+    #[arg(long)]
+    prefer: Option<sync::Side>,
+}
+
+#[derive(clap::Args)]
+struct ArchiveArgs {
+    #[command(subcommand)]
+    command: ArchiveCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum ArchiveCommand {
+    /// Extract every file in an archive.
+    Extract(ArchiveExtractArgs),
+    /// Create an archive from a project.
+    ///
+    /// Files are added in path order, so the same project always gives the same archive.
+    Pack(ArchivePackArgs),
+    /// List the files in an archive, with their size, offset, and key.
+    List(ArchiveListArgs),
+    /// Write a single file from an archive to stdout.
+    Cat(ArchiveCatArgs),
+}
+
+#[derive(clap::Args)]
+struct ArchiveExtractArgs {
+    /// The archive.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    archive: PathBuf,
+    /// The directory to extract to, usually the project directory.
+    #[arg(value_hint = clap_complete::ValueHint::DirPath)]
+    dest: PathBuf,
+    /// The game version to use with `--to`.
+    ///
+    /// Detected from the archive's extension if not specified.
+    game_version: Option<GameVer>,
+    /// Convert data files to this format with the structured representation, instead of
+    /// extracting them as marshal.
+    #[arg(long)]
+    to: Option<Format>,
+}
+
+#[derive(clap::Args)]
+struct ArchivePackArgs {
+    /// The project directory.
+    #[arg(value_hint = clap_complete::ValueHint::DirPath)]
+    project: PathBuf,
+    /// The archive to create.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    archive: PathBuf,
+    /// The archive format version.
+    ///
+    /// Detected from the archive's extension if not specified: 1 for `.rgssad` and `.rgss2a`,
+    /// 3 for `.rgss3a`.
+    #[arg(long = "archive-version")]
+    version: Option<archive::Version>,
+    /// Only pack files matching this glob, relative to the project directory.
+    ///
+    /// Can be specified multiple times. Defaults to `Data/**` and `Graphics/**`, like the editor.
+    #[arg(long)]
+    include: Vec<String>,
+    /// Don't pack files matching this glob, relative to the project directory.
+    ///
+    /// Can be specified multiple times.
+    #[arg(long)]
+    exclude: Vec<String>,
+}
+
+#[derive(clap::Args)]
+struct ArchiveListArgs {
+    /// The archive.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    archive: PathBuf,
+}
+
+#[derive(clap::Args)]
+struct ArchiveCatArgs {
+    /// The archive.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    archive: PathBuf,
+    /// The file's name in the archive, like `Data/Map001.rxdata`.
+    name: String,
+    /// The game version to use with `--to`.
+    ///
+    /// Detected from the archive's extension if not specified.
+    game_version: Option<GameVer>,
+    /// Convert the file to this format with the structured representation.
+    #[arg(long)]
+    to: Option<Format>,
+}
+
+#[derive(clap::Args)]
+struct SaveArgs {
+    #[command(subcommand)]
+    command: SaveCommand,
+}
+
+#[derive(clap::Subcommand)]
+enum SaveCommand {
+    /// Write a save file to a text document.
+    Export(SaveExportArgs),
+    /// Write a text document back to a save file.
+    Import(SaveImportArgs),
+}
+
+#[derive(clap::Args)]
+struct SaveExportArgs {
+    /// The save file, like `Save1.rxdata`.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    save: PathBuf,
+    /// The text document. Its format is determined by the file extension.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    text: PathBuf,
+    /// The game version to use.
+    ///
+    /// Detected from the file extension if not specified.
+    game_version: Option<GameVer>,
+}
+
+#[derive(clap::Args)]
+struct SaveImportArgs {
+    /// The text document. Its format is determined by the file extension.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    text: PathBuf,
+    /// The save file to write.
+    #[arg(value_hint = clap_complete::ValueHint::FilePath)]
+    save: PathBuf,
+    /// The game version to use.
+    ///
+    /// Detected from the file extension if not specified.
+    game_version: Option<GameVer>,
 }
 
 #[allow(clippy::upper_case_acronyms)]
-#[derive(clap::ValueEnum, Clone)]
+#[derive(clap::ValueEnum, Clone, Copy)]
 enum GameVer {
     RPGXP,
+    RPGVX,
+    RPGVXAce,
+}
+
+impl GameVer {
+    fn name(self) -> &'static str {
+        match self {
+            GameVer::RPGXP => "RPG Maker XP",
+            GameVer::RPGVX => "RPG Maker VX",
+            GameVer::RPGVXAce => "RPG Maker VX Ace",
+        }
+    }
 }
 
 #[derive(clap::Args)]
@@ -147,6 +565,14 @@ fn main() {
         Commands::Pack(script_args) => pack::pack(script_args),
         Commands::Unpack(script_args) => unpack::unpack(script_args),
         Commands::Structured(structured_args) => structured::convert(structured_args),
+        Commands::Verify(verify_args) => verify::verify(verify_args),
+        Commands::Events(events_args) => events::events(events_args),
+        Commands::MergeDriver(merge_args) => merge::merge_driver(merge_args),
+        Commands::Diff(diff_args) => diff::diff(diff_args),
+        Commands::Textconv(textconv_args) => textconv::textconv(textconv_args),
+        Commands::Sync(sync_args) => sync::sync(sync_args),
+        Commands::Archive(archive_args) => archive::archive(archive_args),
+        Commands::Save(save_args) => save::save(save_args),
         Commands::Completions(CompletionArgs { shell }) => {
             let mut cmd = Cli::command();
             let name = cmd.get_name().to_owned();
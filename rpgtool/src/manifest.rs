@@ -0,0 +1,160 @@
+//! Incremental conversion.
+//!
+//! A manifest in the destination directory records the hash of every converted source file,
+//! so files that haven't changed since the last run can be skipped.
+
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The manifest's file name. It's never treated as a source file.
+pub const MANIFEST_NAME: &str = ".rpgtool-manifest.json";
+/// The name of the manifest `sync` keeps in the text directory.
+pub const SYNC_MANIFEST_NAME: &str = ".rpgtool-sync.json";
+
+pub fn is_manifest(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == MANIFEST_NAME || name == SYNC_MANIFEST_NAME)
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct Manifest {
+    tool_version: String,
+    /// How the files were converted. If this changes, everything is converted again.
+    settings: String,
+    /// Keyed by path relative to the source directory.
+    files: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct ManifestEntry {
+    hash: String,
+    /// Path relative to the destination directory.
+    output: String,
+}
+
+/// Whether a source file needs converting.
+pub enum Check {
+    UpToDate,
+    /// The hash to record once the file is converted.
+    Changed(String),
+}
+
+pub struct Incremental {
+    path: PathBuf,
+    settings: String,
+    old: BTreeMap<String, ManifestEntry>,
+    new: Mutex<BTreeMap<String, ManifestEntry>>,
+}
+
+fn key(relative: &Path) -> String {
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+/// Hex encoded SHA-256 of a file.
+pub fn hash(path: &Path) -> Result<String, String> {
+    let data = std::fs::read(path).map_err(|e| format!("couldn't open {}: {e}", path.display()))?;
+    let digest = Sha256::digest(data);
+    let mut hash = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        let _ = write!(hash, "{byte:02x}");
+    }
+    Ok(hash)
+}
+
+impl Incremental {
+    /// Loads the manifest in `dest`. A missing or outdated manifest is treated as empty.
+    pub fn load(dest: &Path, settings: String) -> Self {
+        let path = dest.join(MANIFEST_NAME);
+        let manifest: Manifest = std::fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+
+        let old = if manifest.tool_version == env!("CARGO_PKG_VERSION")
+            && manifest.settings == settings
+        {
+            manifest.files
+        } else {
+            BTreeMap::new()
+        };
+
+        Self {
+            path,
+            settings,
+            old,
+            new: Mutex::default(),
+        }
+    }
+
+    /// Checks if `src_path` changed since it was last converted to `dest_path`.
+    pub fn check(
+        &self,
+        relative: &Path,
+        src_path: &Path,
+        dest_path: &Path,
+    ) -> Result<Check, String> {
+        let hash = hash(src_path)?;
+        let key = key(relative);
+
+        match self.old.get(&key) {
+            Some(entry) if entry.hash == hash && dest_path.is_file() => {
+                let mut new = self.new.lock().expect("manifest lock poisoned");
+                new.insert(key, entry.clone());
+                Ok(Check::UpToDate)
+            }
+            _ => Ok(Check::Changed(hash)),
+        }
+    }
+
+    /// Records that `relative` was converted to `output` (relative to the destination directory).
+    pub fn converted(&self, relative: &Path, hash: String, output: &Path) {
+        let mut new = self.new.lock().expect("manifest lock poisoned");
+        new.insert(
+            key(relative),
+            ManifestEntry {
+                hash,
+                output: key(output),
+            },
+        );
+    }
+
+    /// Removes the outputs of deleted source files and writes the manifest.
+    ///
+    /// Returns the outputs that were removed.
+    pub fn finish(self, src: &Path, dest: &Path) -> Result<Vec<PathBuf>, String> {
+        let mut files = self.new.into_inner().expect("manifest lock poisoned");
+
+        let mut removed = vec![];
+        for (key, entry) in self.old {
+            if files.contains_key(&key) {
+                continue;
+            }
+            // sources that still exist were just not converted this time (filtered out, or failed)
+            if src.join(&key).exists() {
+                files.insert(key, entry);
+                continue;
+            }
+
+            let output = dest.join(&entry.output);
+            match std::fs::remove_file(&output) {
+                Ok(()) => removed.push(output),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("couldn't remove {}: {e}", output.display())),
+            }
+        }
+
+        let manifest = Manifest {
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            settings: self.settings,
+            files,
+        };
+        let data = serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, data)
+            .map_err(|e| format!("couldn't write {}: {e}", self.path.display()))?;
+
+        Ok(removed)
+    }
+}
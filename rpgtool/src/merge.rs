@@ -0,0 +1,405 @@
+use clap::{CommandFactory, error::ErrorKind};
+use common::Format;
+use std::collections::BTreeSet;
+
+use crate::structured::{self, DataVisitor, GameData};
+use crate::tree::{self, Path, Segment, Tree};
+use crate::{Cli, MergeDriverArgs};
+
+type Object = serde_json::Map<String, Tree>;
+
+/// A value both sides changed differently.
+struct Conflict {
+    path: Path,
+    base: Option<Tree>,
+    ours: Option<Tree>,
+    theirs: Option<Tree>,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: ours {}, theirs {} (originally {})",
+            self.path,
+            tree::describe(self.ours.as_ref()),
+            tree::describe(self.theirs.as_ref()),
+            tree::describe(self.base.as_ref()),
+        )
+    }
+}
+
+pub fn merge_driver(args: MergeDriverArgs) {
+    let MergeDriverArgs {
+        base,
+        ours,
+        theirs,
+        path,
+        game_version,
+    } = args;
+
+    // git hands us temporary files, so the real name (if we have it) is needed to tell what they are
+    let path = path.unwrap_or_else(|| ours.clone());
+    let detected = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(structured::detect::from_ext);
+    let Some(game_version) = game_version.or(detected) else {
+        let mut command = Cli::command();
+        command
+            .error(
+                ErrorKind::DisplayHelp,
+                "unable to determine game version, please specify it",
+            )
+            .exit()
+    };
+
+    let name = path
+        .file_prefix()
+        .and_then(|prefix| prefix.to_str())
+        .unwrap_or_default();
+    let files = MergeFiles {
+        base: &base,
+        ours: &ours,
+        theirs: &theirs,
+        name,
+    };
+    // unknown files are still merged, just without the structured types
+    let result = structured::dispatch(game_version, name, files)
+        .unwrap_or_else(|| merge_files::<common::Value>(files));
+
+    match result {
+        Ok(conflicts) if conflicts.is_empty() => {}
+        Ok(conflicts) => {
+            for conflict in &conflicts {
+                eprintln!("[CONFLICT]: {conflict}");
+            }
+            eprintln!(
+                "{} conflict(s) in {}, kept our side of each",
+                conflicts.len(),
+                path.display()
+            );
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("[FAIL]: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct MergeFiles<'a> {
+    base: &'a std::path::Path,
+    ours: &'a std::path::Path,
+    theirs: &'a std::path::Path,
+    name: &'a str,
+}
+
+impl DataVisitor for MergeFiles<'_> {
+    type Output = Result<Vec<Conflict>, String>;
+
+    fn visit<T: GameData>(self) -> Self::Output {
+        merge_files::<T>(self)
+    }
+}
+
+/// Merges `theirs` and `base` into `ours`, returning any conflicts.
+fn merge_files<T: GameData>(files: MergeFiles<'_>) -> Result<Vec<Conflict>, String> {
+    let read = |path: &std::path::Path| {
+        tree::read::<T>(path, Format::Marshal)?
+            .map(tree::to_tree)
+            .transpose()
+            .map_err(|e| format!("failed to convert {}: {e}", path.display()))
+    };
+    let base = read(files.base)?;
+    let ours = read(files.ours)?.ok_or_else(|| format!("{} is empty", files.ours.display()))?;
+    let theirs =
+        read(files.theirs)?.ok_or_else(|| format!("{} is empty", files.theirs.display()))?;
+
+    let mut conflicts = vec![];
+    let merged = merge(
+        &Path::new(files.name),
+        base.as_ref(),
+        Some(&ours),
+        Some(&theirs),
+        &mut conflicts,
+    )
+    .unwrap_or(ours);
+
+    let merged: T =
+        tree::from_tree(&merged).map_err(|e| format!("failed to convert merge result: {e}"))?;
+    let output = std::fs::File::create(files.ours)
+        .map_err(|e| format!("couldn't open {}: {e}", files.ours.display()))?;
+    let output = std::io::BufWriter::new(output);
+    common::conv_write(merged, Format::Marshal, output)
+        .map_err(|e| format!("failed to write {}: {e}", files.ours.display()))?;
+
+    Ok(conflicts)
+}
+
+/// Three way merge of a value. `None` means the value isn't there (or was removed).
+///
+/// Conflicting values keep our side.
+fn merge(
+    path: &Path,
+    base: Option<&Tree>,
+    ours: Option<&Tree>,
+    theirs: Option<&Tree>,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Tree> {
+    if ours == theirs || base == theirs {
+        return ours.cloned();
+    }
+    if base == ours {
+        return theirs.cloned();
+    }
+
+    // both sides changed this value, so try and merge what's inside it
+    let merged = match (base, ours, theirs) {
+        (_, Some(ours @ Tree::Object(o)), Some(theirs @ Tree::Object(t)))
+            if base.is_none_or(Tree::is_object) =>
+        {
+            let dims = tree::table_dims(ours);
+            if dims.is_none() && tree::table_dims(theirs).is_none() {
+                let base = base.and_then(Tree::as_object);
+                Some(merge_objects(path, base, o, t, None, conflicts))
+            } else if dims.is_some()
+                && dims == tree::table_dims(theirs)
+                && dims == base.and_then(tree::table_dims)
+            {
+                // tables are merged cell by cell, as long as neither side resized them
+                let base = base.and_then(Tree::as_object);
+                Some(merge_objects(path, base, o, t, dims.as_deref(), conflicts))
+            } else {
+                None
+            }
+        }
+        // commands only make sense together (indents, branches, continued text), so a list
+        // both sides edited can't be merged a command at a time
+        (_, Some(o), Some(t)) if tree::is_command_list(o) || tree::is_command_list(t) => None,
+        (Some(Tree::Array(b)), Some(Tree::Array(o)), Some(Tree::Array(t))) => {
+            merge_arrays(path, b, o, t, conflicts)
+        }
+        _ => None,
+    };
+
+    if merged.is_none() {
+        conflicts.push(Conflict {
+            path: path.clone(),
+            base: base.cloned(),
+            ours: ours.cloned(),
+            theirs: theirs.cloned(),
+        });
+    }
+    merged.or_else(|| ours.cloned())
+}
+
+/// `dims` is set if the objects are tables, so their data is merged by cell.
+fn merge_objects(
+    path: &Path,
+    base: Option<&Object>,
+    ours: &Object,
+    theirs: &Object,
+    dims: Option<&[usize]>,
+    conflicts: &mut Vec<Conflict>,
+) -> Tree {
+    let keys = tree::sorted_keys(
+        ours.keys()
+            .chain(theirs.keys())
+            .chain(base.into_iter().flat_map(Object::keys)),
+    );
+
+    let mut merged = Object::new();
+    for key in keys {
+        let (b, o, t) = (
+            base.and_then(|b| b.get(key)),
+            ours.get(key),
+            theirs.get(key),
+        );
+
+        let value = match (dims, key.as_str(), b, o, t) {
+            // cells are addressed from the table itself, like `Map001.data[3, 4, 0]`
+            (
+                Some(dims),
+                "data",
+                Some(Tree::Array(b)),
+                Some(Tree::Array(o)),
+                Some(Tree::Array(t)),
+            ) => merge_elements(
+                path,
+                b,
+                o,
+                t,
+                |index| Segment::Cell(tree::cell(dims, index)),
+                conflicts,
+            )
+            .map(Tree::Array),
+            _ => merge(&path.join(Segment::Field(key.clone())), b, o, t, conflicts),
+        };
+        if let Some(value) = value {
+            merged.insert(key.clone(), value);
+        }
+    }
+    Tree::Object(merged)
+}
+
+/// Database arrays are merged by id, and any other arrays element by element.
+///
+/// Returns `None` if the arrays can't be merged this way.
+fn merge_arrays(
+    path: &Path,
+    base: &[Tree],
+    ours: &[Tree],
+    theirs: &[Tree],
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Tree> {
+    if let Some(((b, o), t)) = tree::entries(base)
+        .zip(tree::entries(ours))
+        .zip(tree::entries(theirs))
+    {
+        let ids: BTreeSet<i64> = b.keys().chain(o.keys()).chain(t.keys()).copied().collect();
+
+        let mut values = vec![];
+        for id in ids {
+            let path = path.join(Segment::Id(id));
+            let (b, o, t) = (b.get(&id), o.get(&id), t.get(&id));
+            let value = merge(&path, b.copied(), o.copied(), t.copied(), conflicts);
+            values.push((path, [b, o, t], value));
+        }
+        // only entries at the end can be removed, anywhere else every later entry would move
+        while values.last().is_some_and(|(_, _, value)| value.is_none()) {
+            values.pop();
+        }
+
+        let mut merged = vec![];
+        if ours.first().is_some_and(Tree::is_null) {
+            merged.push(Tree::Null);
+        }
+        for (path, [b, o, t], value) in values {
+            let value = value.unwrap_or_else(|| {
+                conflicts.push(Conflict {
+                    path,
+                    base: b.copied().cloned(),
+                    ours: o.copied().cloned(),
+                    theirs: t.copied().cloned(),
+                });
+                // at least one side has it, or it wouldn't have an id
+                o.or(t).or(b).copied().cloned().unwrap_or_default()
+            });
+            merged.push(value);
+        }
+        return Some(Tree::Array(merged));
+    }
+
+    merge_elements(path, base, ours, theirs, Segment::Index, conflicts).map(Tree::Array)
+}
+
+/// Merges arrays of the same length element by element.
+fn merge_elements(
+    path: &Path,
+    base: &[Tree],
+    ours: &[Tree],
+    theirs: &[Tree],
+    segment: impl Fn(usize) -> Segment,
+    conflicts: &mut Vec<Conflict>,
+) -> Option<Vec<Tree>> {
+    if base.len() != ours.len() || ours.len() != theirs.len() {
+        return None;
+    }
+
+    let merged = base
+        .iter()
+        .zip(ours)
+        .zip(theirs)
+        .enumerate()
+        .map(|(index, ((b, o), t))| {
+            merge(
+                &path.join(segment(index)),
+                Some(b),
+                Some(o),
+                Some(t),
+                conflicts,
+            )
+            .expect("both sides have a value")
+        })
+        .collect();
+    Some(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn merge_trees(base: &Tree, ours: &Tree, theirs: &Tree) -> (Tree, Vec<Conflict>) {
+        let mut conflicts = vec![];
+        let merged = merge(
+            &Path::new("Test"),
+            Some(base),
+            Some(ours),
+            Some(theirs),
+            &mut conflicts,
+        )
+        .expect("ours has a value");
+        (merged, conflicts)
+    }
+
+    fn entry(id: i64, name: &str) -> Tree {
+        json!({ "id": id, "name": name })
+    }
+
+    #[test]
+    fn database_entries_by_id() {
+        let base = json!([null, entry(1, "a"), entry(2, "b")]);
+        let ours = json!([null, entry(1, "A"), entry(2, "b")]);
+        let theirs = json!([null, entry(1, "a"), entry(2, "b"), entry(3, "c")]);
+        let (merged, conflicts) = merge_trees(&base, &ours, &theirs);
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            merged,
+            json!([null, entry(1, "A"), entry(2, "b"), entry(3, "c")])
+        );
+    }
+
+    #[test]
+    fn id_gaps() {
+        // ids that aren't their index aren't database entries
+        assert!(tree::entries(&[Tree::Null, entry(1, "a"), entry(3, "c")]).is_none());
+        assert!(tree::entries(&[Tree::Null, entry(1, "a"), entry(2, "b")]).is_some());
+
+        // removing an entry that the other side added after would move it to the removed id
+        let base = json!([null, entry(1, "a"), entry(2, "b")]);
+        let ours = json!([null, entry(1, "a"), entry(2, "b"), entry(3, "c")]);
+        let theirs = json!([null, entry(1, "a")]);
+        let (merged, conflicts) = merge_trees(&base, &ours, &theirs);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path.to_string(), "Test[2]");
+        assert_eq!(merged, ours);
+
+        // at the end it's fine
+        let (merged, conflicts) = merge_trees(&base, &base, &theirs);
+        assert!(conflicts.is_empty());
+        assert_eq!(merged, theirs);
+    }
+
+    #[test]
+    fn command_lists() {
+        let command = |code: u16, indent: usize, text: &str| json!({ "code": code, "indent": indent, "parameters": [text] });
+        let page = |list: Tree| json!({ "list": list, "trigger": 0 });
+
+        let base = page(json!([command(101, 0, "a"), command(101, 0, "b")]));
+        let ours = page(json!([command(101, 0, "A"), command(101, 0, "b")]));
+        let theirs = page(json!([command(101, 0, "a"), command(101, 0, "B")]));
+        let (merged, conflicts) = merge_trees(&base, &ours, &theirs);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path.to_string(), "Test.list");
+        assert_eq!(merged, ours);
+
+        // a list only one side changed is taken as is
+        let theirs = json!({ "list": base["list"], "trigger": 1 });
+        let (merged, conflicts) = merge_trees(&base, &ours, &theirs);
+        assert!(conflicts.is_empty());
+        assert_eq!(merged, json!({ "list": ours["list"], "trigger": 1 }));
+    }
+}
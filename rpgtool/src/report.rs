@@ -0,0 +1,146 @@
+//! Machine-readable summaries of conversion runs.
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::walk;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Ok,
+    Error,
+    /// Not a known game file, so it was converted with the loose representation.
+    Unrecognized,
+    /// Ignored, or unchanged since the last incremental conversion.
+    Skipped,
+}
+
+#[derive(serde::Serialize)]
+struct FileReport {
+    /// Relative to the source directory.
+    path: String,
+    status: Status,
+    /// Relative to the destination directory.
+    output: Option<String>,
+    duration_secs: f64,
+    input_size: Option<u64>,
+    output_size: Option<u64>,
+    error: Option<String>,
+}
+
+#[derive(Default, serde::Serialize)]
+struct Summary {
+    ok: usize,
+    error: usize,
+    unrecognized: usize,
+    skipped: usize,
+}
+
+#[derive(serde::Serialize)]
+struct ReportFile<'a> {
+    summary: Summary,
+    /// Why the run stopped before converting anything.
+    error: Option<&'a str>,
+    files: &'a [FileReport],
+}
+
+/// Collects the status of every file a conversion run looked at.
+#[derive(Default)]
+pub struct Report {
+    files: Mutex<Vec<FileReport>>,
+    error: Option<String>,
+}
+
+fn key(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+impl Report {
+    /// Records what happened to `entry`.
+    ///
+    /// `output` is the output's path, and the same path relative to the destination directory.
+    pub fn record(
+        &self,
+        entry: &walk::Entry,
+        status: Status,
+        started: Instant,
+        output: Option<(&Path, &Path)>,
+        error: Option<String>,
+    ) {
+        let size = |path: &Path| std::fs::metadata(path).ok().map(|meta| meta.len());
+        let output_size = match (status, output) {
+            (Status::Error, _) | (_, None) => None,
+            (_, Some((path, _))) => size(path),
+        };
+
+        let file = FileReport {
+            path: key(&entry.relative),
+            status,
+            output: output.map(|(_, relative)| key(relative)),
+            duration_secs: started.elapsed().as_secs_f64(),
+            input_size: entry.is_file.then(|| size(&entry.path)).flatten(),
+            output_size,
+            error,
+        };
+        self.files.lock().expect("report lock poisoned").push(file);
+    }
+
+    /// Whether the run or any file failed.
+    pub fn failed(&self) -> bool {
+        let files = self.files.lock().expect("report lock poisoned");
+        self.error.is_some() || files.iter().any(|file| file.status == Status::Error)
+    }
+
+    /// Writes the report as JSON, with files in path order.
+    pub fn write(self, path: &Path) -> Result<(), String> {
+        let mut files = self.files.into_inner().expect("report lock poisoned");
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let mut summary = Summary::default();
+        for file in &files {
+            let count = match file.status {
+                Status::Ok => &mut summary.ok,
+                Status::Error => &mut summary.error,
+                Status::Unrecognized => &mut summary.unrecognized,
+                Status::Skipped => &mut summary.skipped,
+            };
+            *count += 1;
+        }
+
+        let report = ReportFile {
+            summary,
+            error: self.error.as_deref(),
+            files: &files,
+        };
+        let data = serde_json::to_vec_pretty(&report).map_err(|e| e.to_string())?;
+        std::fs::write(path, data).map_err(|e| format!("couldn't write {}: {e}", path.display()))
+    }
+}
+
+/// Writes `report` to `path` if one was given, and exits with 1 if any file failed.
+pub fn finish(report: Report, path: Option<&Path>) {
+    let failed = report.failed();
+    if let Some(path) = path
+        && let Err(e) = report.write(path)
+    {
+        eprintln!("failed to write report: {e}");
+        std::process::exit(1);
+    }
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+/// For a run that failed before converting anything: prints `error`, writes a report with it
+/// if `path` was given, and exits with 1.
+pub fn abort(error: String, path: Option<&Path>) -> ! {
+    eprintln!("{error}");
+    let report = Report {
+        files: Mutex::default(),
+        error: Some(error),
+    };
+    finish(report, path);
+    unreachable!("a report with an error always fails")
+}
@@ -0,0 +1,260 @@
+use clap::{CommandFactory, error::ErrorKind};
+use common::Format;
+use common::links::FloatStyle;
+use indexmap::IndexMap;
+use std::path::Path;
+
+use crate::structured;
+use crate::{Cli, GameVer, SaveArgs, SaveCommand, SaveExportArgs, SaveImportArgs};
+
+/// A save file as text, with every value named after what the game dumped.
+type Document = IndexMap<String, common::Value>;
+
+/// What the game dumps into a save file, in order.
+fn layout(game_version: GameVer) -> &'static [&'static str] {
+    match game_version {
+        // Scene_Save#write_save_data
+        GameVer::RPGXP => &[
+            "characters",
+            "frame_count",
+            "game_system",
+            "game_switches",
+            "game_variables",
+            "game_self_switches",
+            "game_screen",
+            "game_actors",
+            "game_party",
+            "game_troop",
+            "game_map",
+            "game_player",
+        ],
+        // Scene_File#write_save_data
+        GameVer::RPGVX => &[
+            "characters",
+            "frame_count",
+            "last_bgm",
+            "last_bgs",
+            "game_system",
+            "game_message",
+            "game_switches",
+            "game_variables",
+            "game_self_switches",
+            "game_actors",
+            "game_party",
+            "game_troop",
+            "game_map",
+            "game_player",
+        ],
+        // DataManager.save_game_without_rescue
+        GameVer::RPGVXAce => &["header", "contents"],
+    }
+}
+
+pub fn save(args: SaveArgs) {
+    let result = match args.command {
+        SaveCommand::Export(args) => export(args),
+        SaveCommand::Import(args) => import(args),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+fn resolve_game_version(game_version: Option<GameVer>, save: &Path) -> GameVer {
+    let detected = save
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(structured::detect::from_ext);
+    let Some(game_version) = game_version.or(detected) else {
+        let mut command = Cli::command();
+        command
+            .error(
+                ErrorKind::DisplayHelp,
+                "unable to determine game version, please specify it",
+            )
+            .exit()
+    };
+    game_version
+}
+
+fn text_format(path: &Path) -> Format {
+    match Format::guess(path) {
+        Some(Format::Marshal) | None => {
+            let mut command = Cli::command();
+            command
+                .error(
+                    ErrorKind::InvalidValue,
+                    format!(
+                        "unable to determine the text format of {}, use a .json, .ron or .yaml file",
+                        path.display()
+                    ),
+                )
+                .exit()
+        }
+        Some(format) => format,
+    }
+}
+
+fn export(args: SaveExportArgs) -> Result<(), String> {
+    let SaveExportArgs {
+        save,
+        text,
+        game_version,
+    } = args;
+    let game_version = resolve_game_version(game_version, &save);
+    let format = text_format(&text);
+
+    let input =
+        std::fs::File::open(&save).map_err(|e| format!("couldn't open {}: {e}", save.display()))?;
+    let input = std::io::BufReader::new(input);
+    // game objects are often referenced from more than one place, keep those links intact
+    let values = common::conv_read_linked_multi(Format::Marshal, input)
+        .map_err(|e| format!("failed to parse {}: {e}", save.display()))?;
+    let document = to_document(values, game_version, &save)?;
+
+    let output = std::fs::File::create(&text)
+        .map_err(|e| format!("couldn't open {}: {e}", text.display()))?;
+    let output = std::io::BufWriter::new(output);
+    common::conv_write(document, format, output)
+        .map_err(|e| format!("failed to write {}: {e}", text.display()))
+}
+
+fn import(args: SaveImportArgs) -> Result<(), String> {
+    let SaveImportArgs {
+        text,
+        save,
+        game_version,
+    } = args;
+    let game_version = resolve_game_version(game_version, &save);
+    let format = text_format(&text);
+
+    let input =
+        std::fs::File::open(&text).map_err(|e| format!("couldn't open {}: {e}", text.display()))?;
+    let input = std::io::BufReader::new(input);
+    let document: Document = common::conv_read(format, input)
+        .map_err(|e| format!("failed to parse {}: {e}", text.display()))?;
+    let values = from_document(document, game_version, &text)?;
+
+    let output = std::fs::File::create(&save)
+        .map_err(|e| format!("couldn't open {}: {e}", save.display()))?;
+    let output = std::io::BufWriter::new(output);
+    common::conv_write_linked_multi(&values, Format::Marshal, float_style(game_version), output)
+        .map_err(|e| format!("failed to write {}: {e}", save.display()))
+}
+
+fn float_style(game_version: GameVer) -> FloatStyle {
+    match game_version {
+        GameVer::RPGXP | GameVer::RPGVX => FloatStyle::Ruby18,
+        GameVer::RPGVXAce => FloatStyle::Shortest,
+    }
+}
+
+/// Names the values read from `save`.
+fn to_document(
+    values: Vec<common::Value>,
+    game_version: GameVer,
+    save: &Path,
+) -> Result<Document, String> {
+    let names = layout(game_version);
+    if values.len() != names.len() {
+        return Err(format!(
+            "{} has {} value(s), but {} saves have {}",
+            save.display(),
+            values.len(),
+            game_version.name(),
+            names.len()
+        ));
+    }
+    Ok(names
+        .iter()
+        .map(|&name| name.to_owned())
+        .zip(values)
+        .collect())
+}
+
+/// The values of a document read from `text`, in the order the game reads them.
+fn from_document(
+    mut document: Document,
+    game_version: GameVer,
+    text: &Path,
+) -> Result<Vec<common::Value>, String> {
+    // the game reads values back in order, so every one has to be there
+    let mut values = vec![];
+    for name in layout(game_version) {
+        let value = document
+            .shift_remove(*name)
+            .ok_or_else(|| format!("{} is missing {name}", text.display()))?;
+        values.push(value);
+    }
+    if let Some(name) = document.keys().next() {
+        return Err(format!(
+            "{} has {name}, which isn't in {} saves",
+            text.display(),
+            game_version.name()
+        ));
+    }
+    Ok(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An XP save with a bignum, a hash with a default value, a float, and a shared object.
+    fn xp_save() -> Vec<u8> {
+        let dumps: [&[u8]; 12] = [
+            b"[\x06[\x07\"\x0a001-Ai\x00",
+            b"l+\x07\x00\x00\x00\x80",
+            b"o:\x10Game_System\x07:\x0a@hash}\x06i\x06i\x07i\x00:\x0c@volumef\x081.5",
+            b"0",
+            b"0",
+            b"0",
+            b"0",
+            b"0",
+            b"0",
+            b"0",
+            b"[\x07o:\x08Foo\x00@\x06",
+            b"0",
+        ];
+        dumps
+            .iter()
+            .flat_map(|dump| [b"\x04\x08", *dump].concat())
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let save = xp_save();
+        for format in [Format::Json, Format::Yaml] {
+            let path = Path::new("Save1.rxdata");
+            let values = common::conv_read_linked_multi(Format::Marshal, save.as_slice())
+                .expect("save should parse");
+            let document = to_document(values, GameVer::RPGXP, path).expect("should be a save");
+
+            let mut text = vec![];
+            common::conv_write(document, format, &mut text).expect("should write text");
+            let document: Document =
+                common::conv_read(format, text.as_slice()).expect("should read text");
+
+            let values = from_document(document, GameVer::RPGXP, path).expect("should be a save");
+            let mut output = vec![];
+            common::conv_write_linked_multi(
+                &values,
+                Format::Marshal,
+                float_style(GameVer::RPGXP),
+                &mut output,
+            )
+            .expect("should write save");
+            assert_eq!(output, save, "{format:?}");
+        }
+    }
+
+    #[test]
+    fn wrong_layout() {
+        let values = common::conv_read_linked_multi(Format::Marshal, xp_save().as_slice())
+            .expect("save should parse");
+        assert!(to_document(values, GameVer::RPGVX, Path::new("Save1.rvdata")).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::GameVer;
+use common::Format;
+use std::path::Path;
+
+/// How a game version was detected, for reporting to the user.
+pub struct Detection {
+    pub game_version: GameVer,
+    pub reason: String,
+}
+
+/// Try to figure out which RPG Maker a project was made with.
+///
+/// In order, this looks at file extensions, the shape of `System`, and then files in the project directory.
+pub fn detect(
+    src: &Path,
+    dest: &Path,
+    from: Format,
+    input_file_ext: Option<&Path>,
+) -> Option<Detection> {
+    let ext_detection = match input_file_ext {
+        Some(ext) => ext
+            .to_str()
+            .and_then(from_ext)
+            .map(|v| (v, ext.to_path_buf())),
+        // the default extension for marshal is always rxdata, so look at what's actually in the directory
+        None => std::fs::read_dir(src)
+            .into_iter()
+            .flatten()
+            .filter_map(Result::ok)
+            .find_map(|entry| {
+                let ext = entry.path().extension()?.to_owned();
+                ext.to_str().and_then(from_ext).map(|v| (v, ext.into()))
+            }),
+    };
+    if let Some((game_version, ext)) = ext_detection {
+        return Some(Detection {
+            game_version,
+            reason: format!("the .{} file extension", ext.display()),
+        });
+    }
+
+    let system_path = src
+        .join("System")
+        .with_extension(input_file_ext.unwrap_or(from.file_ext()));
+    if let Some(game_version) = from_system(&system_path, from) {
+        return Some(Detection {
+            game_version,
+            reason: format!("the fields of {}", system_path.display()),
+        });
+    }
+
+    // Data/ is usually a subdirectory of the project, so check the parent directories too
+    let src = std::path::absolute(src).ok()?;
+    let dest = std::path::absolute(dest).ok()?;
+    let candidates = [
+        Some(src.as_path()),
+        src.parent(),
+        Some(&dest),
+        dest.parent(),
+    ];
+    candidates.into_iter().flatten().find_map(from_project_dir)
+}
+
+pub fn from_ext(ext: &str) -> Option<GameVer> {
+    match ext {
+        "rxdata" | "rgssad" => Some(GameVer::RPGXP),
+        "rvdata" | "rgss2a" => Some(GameVer::RPGVX),
+        "rvdata2" | "rgss3a" => Some(GameVer::RPGVXAce),
+        _ => None,
+    }
+}
+
+fn from_project_dir(dir: &Path) -> Option<Detection> {
+    const MARKERS: &[(&str, GameVer)] = &[
+        ("Game.rxproj", GameVer::RPGXP),
+        ("Game.rgssad", GameVer::RPGXP),
+        ("Game.rvproj", GameVer::RPGVX),
+        ("Game.rgss2a", GameVer::RPGVX),
+        ("Game.rvproj2", GameVer::RPGVXAce),
+        ("Game.rgss3a", GameVer::RPGVXAce),
+    ];
+
+    for &(marker, game_version) in MARKERS {
+        let path = dir.join(marker);
+        if path.is_file() {
+            return Some(Detection {
+                game_version,
+                reason: format!("the presence of {}", path.display()),
+            });
+        }
+    }
+
+    let ini_path = dir.join("Game.ini");
+    let ini = std::fs::read(&ini_path).ok()?;
+    // Game.ini is usually not UTF-8, but the library name always is ASCII
+    let ini = String::from_utf8_lossy(&ini);
+    let library = ini.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("Library")
+            .then(|| value.trim().to_ascii_uppercase())
+    })?;
+
+    // Library is a path like `System\RGSS301.dll` or `RGSS104E.dll`
+    let dll = library.rsplit(['\\', '/']).next()?;
+    let game_version = if dll.starts_with("RGSS1") {
+        GameVer::RPGXP
+    } else if dll.starts_with("RGSS2") {
+        GameVer::RPGVX
+    } else if dll.starts_with("RGSS3") {
+        GameVer::RPGVXAce
+    } else {
+        return None;
+    };
+
+    Some(Detection {
+        game_version,
+        reason: format!("the library specified in {}", ini_path.display()),
+    })
+}
+
+fn from_system(path: &Path, from: Format) -> Option<GameVer> {
+    let file = std::fs::File::open(path).ok()?;
+    let SystemProbe(fields) = common::conv_read(from, std::io::BufReader::new(file)).ok()?;
+    let has = |field: &str| fields.iter().any(|f| f == field);
+
+    if has("words") {
+        Some(GameVer::RPGXP)
+    } else if has("skill_types") || has("currency_unit") {
+        Some(GameVer::RPGVXAce)
+    } else if has("terms") && has("passages") {
+        Some(GameVer::RPGVX)
+    } else {
+        None
+    }
+}
+
+/// Collects the field names of `RPG::System`, regardless of what format it's stored in.
+struct SystemProbe(Vec<String>);
+
+struct ProbeVisitor;
+
+impl<'de> serde::de::Visitor<'de> for ProbeVisitor {
+    type Value = Vec<String>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut fields = vec![];
+        while let Some((key, serde::de::IgnoredAny)) = map.next_entry::<String, _>()? {
+            fields.push(key);
+        }
+        Ok(fields)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for SystemProbe {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(ProbeVisitor).map(Self)
+    }
+}
+
+impl<'de> alox_48::Visitor<'de> for ProbeVisitor {
+    type Value = Vec<String>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("an instance of RPG::System")
+    }
+
+    fn visit_object<A>(
+        self,
+        _class: &'de alox_48::Sym,
+        mut ivars: A,
+    ) -> alox_48::DeResult<Self::Value>
+    where
+        A: alox_48::IvarAccess<'de>,
+    {
+        let mut fields = vec![];
+        while let Some(field) = ivars.next_ivar()? {
+            let field = field.as_str();
+            fields.push(field.strip_prefix('@').unwrap_or(field).to_owned());
+            ivars.next_value::<alox_48::de::Ignored>()?;
+        }
+        Ok(fields)
+    }
+}
+
+impl<'de> alox_48::Deserialize<'de> for SystemProbe {
+    fn deserialize<D>(deserializer: D) -> alox_48::DeResult<Self>
+    where
+        D: alox_48::DeserializerTrait<'de>,
+    {
+        deserializer.deserialize(ProbeVisitor).map(Self)
+    }
+}
@@ -6,6 +6,8 @@
     unused_imports
 )]
 
+pub mod detect;
+
 mod nil_padded;
 pub use nil_padded::NilPadded;
 
@@ -15,24 +17,20 @@ pub use rgss_structs::*;
 mod shared;
 pub use shared::*;
 
+pub mod rmvx;
+pub mod rmvxa;
 pub mod rmxp;
 
-use crate::{Cli, ConvArgs, GameVer, StructuredArgs};
+use crate::report::{self, Status};
+use crate::{Cli, ConvArgs, GameVer, StructuredArgs, manifest, walk};
 
 use clap::{CommandFactory, error::ErrorKind};
 use common::Format;
 use indicatif::ProgressStyle;
 use rayon::prelude::*;
 use std::path::PathBuf;
-
-macro_rules! fail {
-    ($pb:expr, $fail_on_error:expr) => {
-        if $fail_on_error {
-            $pb.abandon();
-            return None;
-        }
-    };
-}
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
 
 #[allow(clippy::too_many_lines)]
 pub fn convert(args: StructuredArgs) {
@@ -46,6 +44,13 @@ pub fn convert(args: StructuredArgs) {
         output_file_ext,
         single_thread,
         thread_count,
+        typed_commands,
+        typed_move_commands,
+        recursive,
+        include,
+        exclude,
+        incremental,
+        report: report_path,
     } = args;
 
     if let Some(count) = thread_count {
@@ -76,37 +81,67 @@ pub fn convert(args: StructuredArgs) {
         _ => unreachable!(), // we enforce the number of values in clap
     };
 
+    let game_version = if let Some(game_version) = game_version {
+        game_version
+    } else {
+        let detection = detect::detect(&src, &dest, from, input_file_ext.as_deref());
+        let Some(detection) = detection else {
+            let mut command = Cli::command();
+            command
+                .error(
+                    ErrorKind::DisplayHelp,
+                    "unable to determine game version, please specify it",
+                )
+                .exit()
+        };
+        eprintln!(
+            "[INFO]: Detected {} from {}",
+            detection.game_version.name(),
+            detection.reason
+        );
+        detection.game_version
+    };
+
     let input_file_ext = input_file_ext.as_deref().unwrap_or(from.file_ext());
     let output_file_ext = output_file_ext.as_deref().unwrap_or(to.file_ext());
 
+    // the typed commands are RGSS1's, later versions reuse codes with different parameters
+    let (typed_commands, typed_move_commands) =
+        if (typed_commands || typed_move_commands) && !matches!(game_version, GameVer::RPGXP) {
+            eprintln!("[WARN]: Typed commands are only supported for RPG Maker XP, ignoring");
+            (false, false)
+        } else {
+            (typed_commands, typed_move_commands)
+        };
+
     // try and create the destination dir, and if it doesn't exist then error out
     if let Err(e) = std::fs::create_dir(&dest)
         && e.kind() != std::io::ErrorKind::AlreadyExists
     {
-        eprintln!("failed to create destination directory: {e}");
-        return;
+        let error = format!("failed to create destination directory: {e}");
+        report::abort(error, report_path.as_deref());
     }
 
-    let read_dir = match std::fs::read_dir(&src) {
-        Ok(r) => r,
-        Err(e) => {
-            eprintln!("failed to read {}: {e}", src.display());
-            return;
-        }
+    let filter = match walk::Filter::new(&include, &exclude) {
+        Ok(filter) => filter,
+        Err(e) => report::abort(format!("invalid glob: {e}"), report_path.as_deref()),
     };
-
-    let mut entries: Vec<_> = if fail_on_error {
-        match read_dir.collect() {
-            Ok(e) => e,
-            Err(e) => {
-                eprintln!("failed to read directory entry: {e}");
-                return;
-            }
-        }
-    } else {
-        read_dir.filter_map(Result::ok).collect()
+    let entries = match walk::entries(&src, recursive, &filter, fail_on_error) {
+        Ok(entries) => entries,
+        Err(e) => report::abort(e, report_path.as_deref()),
     };
-    entries.sort_by_key(std::fs::DirEntry::path);
+
+    let incremental = incremental.then(|| {
+        let settings = format!(
+            "structured {} {from:?} (.{}) to {to:?} (.{}), typed commands: {typed_commands}, typed move commands: {typed_move_commands}",
+            game_version.name(),
+            input_file_ext.display(),
+            output_file_ext.display()
+        );
+        manifest::Incremental::load(&dest, settings)
+    });
+    let skipped = AtomicUsize::new(0);
+    let report = report::Report::default();
 
     let pb = indicatif::ProgressBar::new(entries.len() as _);
     pb.set_style(
@@ -118,41 +153,85 @@ pub fn convert(args: StructuredArgs) {
     );
     pb.enable_steady_tick(std::time::Duration::from_millis(50));
 
-    let entry_fn = |entry: &std::fs::DirEntry| {
+    let entry_fn = |entry: &walk::Entry| {
         pb.inc(1);
-        let src_path = entry.path();
+        let started = Instant::now();
+        let src_path = &entry.path;
         // if not a file *or* the file extension does not match what it should, print warning and continue
-        if !entry.file_type().expect("couldn't get file type").is_file()
-            || src_path.extension().is_none_or(|ext| ext != input_file_ext)
-        {
+        if !entry.is_file || src_path.extension().is_none_or(|ext| ext != input_file_ext) {
             pb.println(format!("[WARN]: Ignoring {}", src_path.display()));
+            report.record(entry, Status::Skipped, started, None, None);
             return Some(());
         }
 
-        let filename = src_path.file_name().expect("entry should have a file name");
-        let dest_path = dest.join(filename).with_extension(output_file_ext);
-
-        let result = match game_version {
-            GameVer::RPGXP => convert_xp(&src_path, &dest_path, to, from),
+        let output = entry.relative.with_extension(output_file_ext);
+        let dest_path = dest.join(&output);
+        let failed = |e: String| {
+            let output = Some((dest_path.as_path(), output.as_path()));
+            report.record(entry, Status::Error, started, output, Some(e.clone()));
+            pb.println(e);
+            if fail_on_error {
+                pb.abandon();
+                return None;
+            }
+            Some(())
         };
 
-        match result {
-            ConvertResult::Ok => {}
-            ConvertResult::Err(e) => {
-                pb.println(e);
-                fail!(pb, fail_on_error);
-            }
-            ConvertResult::Unrecognized => {
-                pb.println(format!("unrecognized file {}", src_path.display()));
-                fail!(pb, fail_on_error);
+        if let Some(parent) = dest_path.parent()
+            && let Err(e) = std::fs::create_dir_all(parent)
+        {
+            return failed(format!("failed to create {}: {e}", parent.display()));
+        }
 
-                if let Err(e) = convert_data::<common::Value>(&src_path, &dest_path, to, from) {
-                    pb.println(e);
-                    fail!(pb, fail_on_error);
+        let mut hash = None;
+        if let Some(incremental) = &incremental {
+            match incremental.check(&entry.relative, src_path, &dest_path) {
+                Ok(manifest::Check::UpToDate) => {
+                    skipped.fetch_add(1, Ordering::Relaxed);
+                    let output = Some((dest_path.as_path(), output.as_path()));
+                    report.record(entry, Status::Skipped, started, output, None);
+                    return Some(());
                 }
+                Ok(manifest::Check::Changed(new_hash)) => hash = Some(new_hash),
+                Err(e) => return failed(e),
             }
         }
 
+        let prefix = src_path.file_prefix().expect("there should be a prefix");
+        let Some(filename) = prefix.to_str() else {
+            return failed(format!("{} is not valid UTF-8", prefix.display()));
+        };
+
+        set_typed_event_commands(typed_commands);
+        set_typed_move_commands(typed_move_commands);
+        let visitor = ConvertFile {
+            src_path,
+            dest_path: &dest_path,
+            to,
+            from,
+        };
+        let mut status = Status::Ok;
+        let result = if let Some(result) = dispatch(game_version, filename, visitor) {
+            result
+        } else {
+            let message = format!("unrecognized file {}", src_path.display());
+            if fail_on_error {
+                return failed(message);
+            }
+            pb.println(message);
+
+            status = Status::Unrecognized;
+            convert_data::<common::Value>(src_path, &dest_path, to, from)
+        };
+        if let Err(e) = result {
+            return failed(e);
+        }
+
+        if let (Some(incremental), Some(hash)) = (&incremental, hash) {
+            incremental.converted(&entry.relative, hash, &output);
+        }
+        let output = Some((dest_path.as_path(), output.as_path()));
+        report.record(entry, status, started, output, None);
         Some(())
     };
 
@@ -165,67 +244,216 @@ pub fn convert(args: StructuredArgs) {
     if result.is_some() {
         pb.finish();
     }
+
+    if let Some(incremental) = incremental {
+        let skipped = skipped.into_inner();
+        if skipped > 0 {
+            eprintln!("[INFO]: Skipped {skipped} unchanged file(s)");
+        }
+        match incremental.finish(&src, &dest) {
+            Ok(removed) => {
+                for path in removed {
+                    eprintln!("[INFO]: Removed {}", path.display());
+                }
+            }
+            Err(e) => eprintln!("failed to update manifest: {e}"),
+        }
+    }
+
+    report::finish(report, report_path.as_deref());
 }
 
-enum ConvertResult {
-    Ok,
-    Err(String),
-    Unrecognized,
+/// Any game data type that can be converted between formats.
+pub trait GameData:
+    for<'de> serde::Deserialize<'de>
+    + serde::Serialize
+    + for<'de> alox_48::Deserialize<'de>
+    + alox_48::Serialize
+{
+}
+
+impl<T> GameData for T where
+    T: for<'de> serde::Deserialize<'de>
+        + serde::Serialize
+        + for<'de> alox_48::Deserialize<'de>
+        + alox_48::Serialize
+{
 }
 
-fn convert_xp(
+/// Does something with the structured type of a game file. See [`dispatch`].
+pub trait DataVisitor {
+    type Output;
+
+    fn visit<T: GameData>(self) -> Self::Output;
+}
+
+/// Calls `visitor` with the structured type `filename` (without extension) has in `game_version`.
+///
+/// Returns `None` if the file isn't recognized.
+/// String parameters are set to be written the way `game_version` writes them.
+pub fn dispatch<V: DataVisitor>(
+    game_version: GameVer,
+    filename: &str,
+    visitor: V,
+) -> Option<V::Output> {
+    set_encoded_string_parameters(matches!(game_version, GameVer::RPGVXAce));
+    match game_version {
+        GameVer::RPGXP => dispatch_xp(filename, visitor),
+        GameVer::RPGVX => dispatch_vx(filename, visitor),
+        GameVer::RPGVXAce => dispatch_vxa(filename, visitor),
+    }
+}
+
+fn dispatch_xp<V: DataVisitor>(filename: &str, visitor: V) -> Option<V::Output> {
+    let output = match filename {
+        "Actors" => visitor.visit::<rmxp::Actors>(),
+        "Animations" => visitor.visit::<rmxp::Animations>(),
+        "Armors" => visitor.visit::<rmxp::Armors>(),
+        "Classes" => visitor.visit::<rmxp::Classes>(),
+        "Enemies" => visitor.visit::<rmxp::Enemies>(),
+        "Items" => visitor.visit::<rmxp::Items>(),
+        "Skills" => visitor.visit::<rmxp::Skills>(),
+        "States" => visitor.visit::<rmxp::States>(),
+        "System" => visitor.visit::<rmxp::System>(),
+        "Tilesets" => visitor.visit::<rmxp::Tilesets>(),
+        "Troops" => visitor.visit::<rmxp::Troops>(),
+        "Weapons" => visitor.visit::<rmxp::Weapons>(),
+        "MapInfos" => visitor.visit::<shared::MapInfos>(),
+        "CommonEvents" => visitor.visit::<shared::CommonEvents>(),
+        _ if filename.starts_with("Map") => visitor.visit::<rmxp::Map>(),
+        "Scripts" | "xScripts" => visitor.visit::<Vec<shared::Script>>(),
+        _ => return None,
+    };
+    Some(output)
+}
+
+fn dispatch_vx<V: DataVisitor>(filename: &str, visitor: V) -> Option<V::Output> {
+    let output = match filename {
+        "Actors" => visitor.visit::<rmvx::Actors>(),
+        "Animations" => visitor.visit::<rmvx::Animations>(),
+        "Areas" => visitor.visit::<rmvx::Areas>(),
+        "Armors" => visitor.visit::<rmvx::Armors>(),
+        "Classes" => visitor.visit::<rmvx::Classes>(),
+        "Enemies" => visitor.visit::<rmvx::Enemies>(),
+        "Items" => visitor.visit::<rmvx::Items>(),
+        "Skills" => visitor.visit::<rmvx::Skills>(),
+        "States" => visitor.visit::<rmvx::States>(),
+        "System" => visitor.visit::<rmvx::System>(),
+        "Troops" => visitor.visit::<rmvx::Troops>(),
+        "Weapons" => visitor.visit::<rmvx::Weapons>(),
+        "MapInfos" => visitor.visit::<shared::MapInfos>(),
+        "CommonEvents" => visitor.visit::<shared::CommonEvents>(),
+        _ if filename.starts_with("Map") => visitor.visit::<rmvx::Map>(),
+        "Scripts" => visitor.visit::<Vec<shared::Script>>(),
+        _ => return None,
+    };
+    Some(output)
+}
+
+fn dispatch_vxa<V: DataVisitor>(filename: &str, visitor: V) -> Option<V::Output> {
+    let output = match filename {
+        "Actors" => visitor.visit::<rmvxa::Actors>(),
+        "Animations" => visitor.visit::<rmvxa::Animations>(),
+        "Armors" => visitor.visit::<rmvxa::Armors>(),
+        "Classes" => visitor.visit::<rmvxa::Classes>(),
+        "Enemies" => visitor.visit::<rmvxa::Enemies>(),
+        "Items" => visitor.visit::<rmvxa::Items>(),
+        "Skills" => visitor.visit::<rmvxa::Skills>(),
+        "States" => visitor.visit::<rmvxa::States>(),
+        "System" => visitor.visit::<rmvxa::System>(),
+        "Tilesets" => visitor.visit::<rmvxa::Tilesets>(),
+        "Troops" => visitor.visit::<rmvxa::Troops>(),
+        "Weapons" => visitor.visit::<rmvxa::Weapons>(),
+        "MapInfos" => visitor.visit::<shared::MapInfos>(),
+        "CommonEvents" => visitor.visit::<shared::CommonEvents>(),
+        _ if filename.starts_with("Map") => visitor.visit::<rmvxa::Map>(),
+        "Scripts" => visitor.visit::<Vec<shared::Script>>(),
+        _ => return None,
+    };
+    Some(output)
+}
+
+/// Converts a single file, using its structured type if it has one.
+pub fn convert_file(
+    game_version: GameVer,
     src_path: &std::path::Path,
     dest_path: &std::path::Path,
-    to: Format,
     from: Format,
-) -> ConvertResult {
-    let prefix = src_path.file_prefix().expect("there should be a prefix");
-    let Some(filename) = prefix.to_str() else {
-        return ConvertResult::Err(format!("{} is not valid UTF-8", prefix.display()));
+    to: Format,
+) -> Result<(), String> {
+    let filename = src_path
+        .file_prefix()
+        .and_then(|prefix| prefix.to_str())
+        .unwrap_or_default();
+    let visitor = ConvertFile {
+        src_path,
+        dest_path,
+        to,
+        from,
     };
+    dispatch(game_version, filename, visitor)
+        .unwrap_or_else(|| convert_data::<common::Value>(src_path, dest_path, to, from))
+}
 
-    let result = match filename {
-        "Actors" => convert_data::<rmxp::Actors>(src_path, dest_path, to, from),
-        "Animations" => convert_data::<rmxp::Animations>(src_path, dest_path, to, from),
-        "Armors" => convert_data::<rmxp::Armors>(src_path, dest_path, to, from),
-        "Classes" => convert_data::<rmxp::Classes>(src_path, dest_path, to, from),
-        "Enemies" => convert_data::<rmxp::Enemies>(src_path, dest_path, to, from),
-        "Items" => convert_data::<rmxp::Items>(src_path, dest_path, to, from),
-        "Skills" => convert_data::<rmxp::Skills>(src_path, dest_path, to, from),
-        "States" => convert_data::<rmxp::States>(src_path, dest_path, to, from),
-        "System" => convert_data::<rmxp::System>(src_path, dest_path, to, from),
-        "Tilesets" => convert_data::<rmxp::Tilesets>(src_path, dest_path, to, from),
-        "Troops" => convert_data::<rmxp::Troops>(src_path, dest_path, to, from),
-        "Weapons" => convert_data::<rmxp::Weapons>(src_path, dest_path, to, from),
-        "MapInfos" => convert_data::<shared::MapInfos>(src_path, dest_path, to, from),
-        "CommonEvents" => convert_data::<shared::CommonEvents>(src_path, dest_path, to, from),
-        _ if filename.starts_with("Map") => {
-            convert_data::<rmxp::Map>(src_path, dest_path, to, from)
-        }
-        "Scripts" | "xScripts" => {
-            convert_data::<Vec<shared::Script>>(src_path, dest_path, to, from)
-        }
-        _ => return ConvertResult::Unrecognized,
-    };
+/// Like [`convert_file`], but in memory. `filename` is the file's name without an extension.
+pub fn convert_bytes(
+    game_version: GameVer,
+    filename: &str,
+    data: &[u8],
+    from: Format,
+    to: Format,
+) -> Result<Vec<u8>, common::ConvError> {
+    let visitor = ConvertBytes { data, from, to };
+    dispatch(game_version, filename, visitor)
+        .unwrap_or_else(|| convert_bytes_as::<common::Value>(data, from, to))
+}
+
+struct ConvertBytes<'a> {
+    data: &'a [u8],
+    from: Format,
+    to: Format,
+}
+
+impl DataVisitor for ConvertBytes<'_> {
+    type Output = Result<Vec<u8>, common::ConvError>;
+
+    fn visit<T: GameData>(self) -> Self::Output {
+        convert_bytes_as::<T>(self.data, self.from, self.to)
+    }
+}
+
+fn convert_bytes_as<T: GameData>(
+    data: &[u8],
+    from: Format,
+    to: Format,
+) -> Result<Vec<u8>, common::ConvError> {
+    let value: T = common::conv_read(from, data)?;
+    let mut output = vec![];
+    common::conv_write(value, to, &mut output)?;
+    Ok(output)
+}
+
+struct ConvertFile<'a> {
+    src_path: &'a std::path::Path,
+    dest_path: &'a std::path::Path,
+    to: Format,
+    from: Format,
+}
 
-    match result {
-        Ok(()) => ConvertResult::Ok,
-        Result::Err(e) => ConvertResult::Err(e),
+impl DataVisitor for ConvertFile<'_> {
+    type Output = Result<(), String>;
+
+    fn visit<T: GameData>(self) -> Self::Output {
+        convert_data::<T>(self.src_path, self.dest_path, self.to, self.from)
     }
 }
 
-fn convert_data<T>(
+fn convert_data<T: GameData>(
     src_path: &std::path::Path,
     dest_path: &std::path::Path,
     to: Format,
     from: Format,
-) -> Result<(), String>
-where
-    T: for<'de> serde::Deserialize<'de>
-        + serde::Serialize
-        + for<'de> alox_48::Deserialize<'de>
-        + alox_48::Serialize,
-{
+) -> Result<(), String> {
     let input = std::fs::File::open(src_path)
         .map_err(|e| format!("couldn't open {}: {e}", src_path.display()))?;
     let input = std::io::BufReader::new(input);
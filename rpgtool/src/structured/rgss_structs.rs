@@ -1,9 +1,76 @@
 #![allow(missing_docs)]
 
+/// Reads userdata that is just the bytes of `T`, like a `Color`.
+fn read_pod<T: bytemuck::Pod>(value: &alox_48::Userdata) -> Result<T, alox_48::DeError> {
+    bytemuck::try_pod_read_unaligned(&value.data).map_err(|_| {
+        alox_48::DeError::custom(format!(
+            "expected {} bytes of {} data, got {}",
+            std::mem::size_of::<T>(),
+            value.class,
+            value.data.len()
+        ))
+    })
+}
+
+/// Reads the data of a marshaled `Table`, checking that it has `dims` dimensions.
+///
+/// Returns the x, y, and z sizes along with the data.
+fn read_table(
+    value: &alox_48::Userdata,
+    dims: u32,
+) -> Result<([usize; 3], Vec<i16>), alox_48::DeError> {
+    const HEADER_LEN: usize = std::mem::size_of::<u32>() * 5;
+
+    let Some((header, data)) = value.data.split_at_checked(HEADER_LEN) else {
+        return Err(alox_48::DeError::custom(format!(
+            "table header is truncated, got {} bytes",
+            value.data.len()
+        )));
+    };
+    let [table_dims, xsize, ysize, zsize, len]: [u32; 5] = bytemuck::pod_read_unaligned(header);
+    if table_dims != dims {
+        return Err(alox_48::DeError::custom(format!(
+            "expected a {dims}D table, got a {table_dims}D table"
+        )));
+    }
+
+    let [xsize, ysize, zsize, len] = [xsize, ysize, zsize, len].map(|n| n as usize);
+    check_table_size([xsize, ysize, zsize], len).map_err(alox_48::DeError::custom)?;
+    if data.len() != len * std::mem::size_of::<i16>() {
+        return Err(alox_48::DeError::custom(format!(
+            "table should have {len} elements but has {} bytes of data",
+            data.len()
+        )));
+    }
+
+    Ok(([xsize, ysize, zsize], bytemuck::pod_collect_to_vec(data)))
+}
+
+/// Checks that a table with these sizes has `len` elements, and that it fits in a marshaled table.
+fn check_table_size([xsize, ysize, zsize]: [usize; 3], len: usize) -> Result<(), String> {
+    let size = xsize
+        .checked_mul(ysize)
+        .and_then(|size| size.checked_mul(zsize));
+    if size != Some(len) {
+        return Err(format!(
+            "table is {xsize}x{ysize}x{zsize} but has {len} elements"
+        ));
+    }
+    if [xsize, ysize, zsize, len]
+        .into_iter()
+        .any(|n| u32::try_from(n).is_err())
+    {
+        return Err(format!(
+            "table is {xsize}x{ysize}x{zsize}, which is too large for RGSS"
+        ));
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 #[derive(serde::Serialize, serde::Deserialize)]
 #[derive(alox_48::Deserialize, alox_48::Serialize)]
-#[marshal(from = "alox_48::Userdata", into = "alox_48::Userdata")]
+#[marshal(try_from = "alox_48::Userdata", into = "alox_48::Userdata")]
 #[derive(bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct Color {
@@ -13,9 +80,11 @@ pub struct Color {
     pub alpha: f64,
 }
 
-impl From<alox_48::Userdata> for Color {
-    fn from(value: alox_48::Userdata) -> Self {
-        *bytemuck::from_bytes(&value.data)
+impl TryFrom<alox_48::Userdata> for Color {
+    type Error = alox_48::DeError;
+
+    fn try_from(value: alox_48::Userdata) -> Result<Self, Self::Error> {
+        read_pod(&value)
     }
 }
 
@@ -49,7 +118,7 @@ impl Default for Color {
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
 #[derive(serde::Serialize, serde::Deserialize)]
 #[derive(alox_48::Deserialize, alox_48::Serialize)]
-#[marshal(from = "alox_48::Userdata", into = "alox_48::Userdata")]
+#[marshal(try_from = "alox_48::Userdata", into = "alox_48::Userdata")]
 #[derive(bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct Tone {
@@ -59,9 +128,11 @@ pub struct Tone {
     pub gray: f64,
 }
 
-impl From<alox_48::Userdata> for Tone {
-    fn from(value: alox_48::Userdata) -> Self {
-        *bytemuck::from_bytes(&value.data)
+impl TryFrom<alox_48::Userdata> for Tone {
+    type Error = alox_48::DeError;
+
+    fn try_from(value: alox_48::Userdata) -> Result<Self, Self::Error> {
+        read_pod(&value)
     }
 }
 
@@ -80,34 +151,73 @@ impl From<Tone> for alox_48::Value {
     }
 }
 
+/// Only serialized by RGSS2 and up.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(try_from = "alox_48::Userdata", into = "alox_48::Userdata")]
+#[derive(bytemuck::Pod, bytemuck::Zeroable)]
+#[repr(C)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl TryFrom<alox_48::Userdata> for Rect {
+    type Error = alox_48::DeError;
+
+    fn try_from(value: alox_48::Userdata) -> Result<Self, Self::Error> {
+        read_pod(&value)
+    }
+}
+
+impl From<Rect> for alox_48::Userdata {
+    fn from(value: Rect) -> Self {
+        alox_48::Userdata {
+            class: "Rect".into(),
+            data: bytemuck::bytes_of(&value).to_vec(),
+        }
+    }
+}
+
 /// Normal RGSS has dynamically dimensioned arrays, but in practice that does not map well to Rust.
 /// We don't particularly need dynamically sized arrays anyway.
 /// 1D Table.
 #[derive(Debug, Default, Clone)]
 #[derive(serde::Serialize, serde::Deserialize)]
 #[derive(alox_48::Deserialize, alox_48::Serialize)]
-#[marshal(from = "alox_48::Userdata", into = "alox_48::Userdata")]
+#[serde(try_from = "TextTable1")]
+#[marshal(try_from = "alox_48::Userdata", into = "alox_48::Userdata")]
 pub struct Table1 {
     xsize: usize,
     data: Vec<i16>,
 }
 
-impl From<alox_48::Userdata> for Table1 {
-    fn from(value: alox_48::Userdata) -> Self {
-        let u32_slice: &[u32] =
-            bytemuck::cast_slice(&value.data[0..std::mem::size_of::<u32>() * 5]);
+/// A [`Table1`] read from a text format, which may not add up yet.
+#[derive(serde::Deserialize)]
+struct TextTable1 {
+    xsize: usize,
+    data: Vec<i16>,
+}
+
+impl TryFrom<TextTable1> for Table1 {
+    type Error = String;
 
-        assert_eq!(u32_slice[0], 1);
-        let xsize = u32_slice[1] as usize;
-        let ysize = u32_slice[2] as usize;
-        let zsize = u32_slice[3] as usize;
-        let len = u32_slice[4] as usize;
+    fn try_from(value: TextTable1) -> Result<Self, Self::Error> {
+        let TextTable1 { xsize, data } = value;
+        check_table_size([xsize, 1, 1], data.len())?;
+        Ok(Self { xsize, data })
+    }
+}
 
-        assert_eq!(xsize * ysize * zsize, len);
-        let data = bytemuck::cast_slice(&value.data[(std::mem::size_of::<u32>() * 5)..]).to_vec();
-        assert_eq!(data.len(), len);
+impl TryFrom<alox_48::Userdata> for Table1 {
+    type Error = alox_48::DeError;
 
-        Self { xsize, data }
+    fn try_from(value: alox_48::Userdata) -> Result<Self, Self::Error> {
+        let ([xsize, _, _], data) = read_table(&value, 1)?;
+        Ok(Self { xsize, data })
     }
 }
 
@@ -128,29 +238,37 @@ impl From<Table1> for alox_48::Userdata {
 #[derive(Debug, Default, Clone)]
 #[derive(serde::Serialize, serde::Deserialize)]
 #[derive(alox_48::Deserialize, alox_48::Serialize)]
-#[marshal(from = "alox_48::Userdata", into = "alox_48::Userdata")]
+#[serde(try_from = "TextTable2")]
+#[marshal(try_from = "alox_48::Userdata", into = "alox_48::Userdata")]
 pub struct Table2 {
     xsize: usize,
     ysize: usize,
     data: Vec<i16>,
 }
 
-impl From<alox_48::Userdata> for Table2 {
-    fn from(value: alox_48::Userdata) -> Self {
-        let u32_slice: &[u32] =
-            bytemuck::cast_slice(&value.data[0..std::mem::size_of::<u32>() * 5]);
+#[derive(serde::Deserialize)]
+struct TextTable2 {
+    xsize: usize,
+    ysize: usize,
+    data: Vec<i16>,
+}
 
-        assert_eq!(u32_slice[0], 2);
-        let xsize = u32_slice[1] as usize;
-        let ysize = u32_slice[2] as usize;
-        let zsize = u32_slice[3] as usize;
-        let len = u32_slice[4] as usize;
+impl TryFrom<TextTable2> for Table2 {
+    type Error = String;
 
-        assert_eq!(xsize * ysize * zsize, len);
-        let data = bytemuck::cast_slice(&value.data[(std::mem::size_of::<u32>() * 5)..]).to_vec();
-        assert_eq!(data.len(), len);
+    fn try_from(value: TextTable2) -> Result<Self, Self::Error> {
+        let TextTable2 { xsize, ysize, data } = value;
+        check_table_size([xsize, ysize, 1], data.len())?;
+        Ok(Self { xsize, ysize, data })
+    }
+}
+
+impl TryFrom<alox_48::Userdata> for Table2 {
+    type Error = alox_48::DeError;
 
-        Self { xsize, ysize, data }
+    fn try_from(value: alox_48::Userdata) -> Result<Self, Self::Error> {
+        let ([xsize, ysize, _], data) = read_table(&value, 2)?;
+        Ok(Self { xsize, ysize, data })
     }
 }
 
@@ -176,7 +294,8 @@ impl From<Table2> for alox_48::Userdata {
 #[derive(Debug, Default, Clone)]
 #[derive(serde::Serialize, serde::Deserialize)]
 #[derive(alox_48::Deserialize, alox_48::Serialize)]
-#[marshal(from = "alox_48::Userdata", into = "alox_48::Userdata")]
+#[serde(try_from = "TextTable3")]
+#[marshal(try_from = "alox_48::Userdata", into = "alox_48::Userdata")]
 /// 3D table. See [`Table2`].
 pub struct Table3 {
     xsize: usize,
@@ -185,27 +304,45 @@ pub struct Table3 {
     data: Vec<i16>,
 }
 
-impl From<alox_48::Userdata> for Table3 {
-    fn from(value: alox_48::Userdata) -> Self {
-        let u32_slice: &[u32] =
-            bytemuck::cast_slice(&value.data[0..std::mem::size_of::<u32>() * 5]);
+#[derive(serde::Deserialize)]
+struct TextTable3 {
+    xsize: usize,
+    ysize: usize,
+    zsize: usize,
+    data: Vec<i16>,
+}
 
-        assert_eq!(u32_slice[0], 3);
-        let xsize = u32_slice[1] as usize;
-        let ysize = u32_slice[2] as usize;
-        let zsize = u32_slice[3] as usize;
-        let len = u32_slice[4] as usize;
+impl TryFrom<TextTable3> for Table3 {
+    type Error = String;
 
-        assert_eq!(xsize * ysize * zsize, len);
-        let data = bytemuck::cast_slice(&value.data[(std::mem::size_of::<u32>() * 5)..]).to_vec();
-        assert_eq!(data.len(), len);
+    fn try_from(value: TextTable3) -> Result<Self, Self::Error> {
+        let TextTable3 {
+            xsize,
+            ysize,
+            zsize,
+            data,
+        } = value;
+        check_table_size([xsize, ysize, zsize], data.len())?;
+        Ok(Self {
+            xsize,
+            ysize,
+            zsize,
+            data,
+        })
+    }
+}
 
-        Self {
+impl TryFrom<alox_48::Userdata> for Table3 {
+    type Error = alox_48::DeError;
+
+    fn try_from(value: alox_48::Userdata) -> Result<Self, Self::Error> {
+        let ([xsize, ysize, zsize], data) = read_table(&value, 3)?;
+        Ok(Self {
             xsize,
             ysize,
             zsize,
             data,
-        }
+        })
     }
 }
 
@@ -227,3 +364,89 @@ impl From<Table3> for alox_48::Userdata {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(header: [u32; 5], data: &[i16]) -> alox_48::Userdata {
+        let mut bytes = bytemuck::pod_collect_to_vec(&header);
+        bytes.extend_from_slice(bytemuck::cast_slice(data));
+        alox_48::Userdata {
+            class: "Table".into(),
+            data: bytes,
+        }
+    }
+
+    fn marshal_error<T>(value: alox_48::Userdata) -> String
+    where
+        T: TryFrom<alox_48::Userdata, Error = alox_48::DeError> + std::fmt::Debug,
+    {
+        T::try_from(value)
+            .expect_err("table is invalid")
+            .to_string()
+    }
+
+    #[test]
+    fn marshal_tables() {
+        let value = table([3, 2, 1, 2, 4], &[1, 2, 3, 4]);
+        let table3 = Table3::try_from(value.clone()).expect("table is valid");
+        assert_eq!(alox_48::Userdata::from(table3).data, value.data);
+
+        let mut truncated = table([1, 2, 1, 1, 2], &[1, 2]);
+        truncated.data.truncate(10);
+        let error = marshal_error::<Table1>(truncated);
+        assert!(
+            error.contains("table header is truncated, got 10 bytes"),
+            "{error}"
+        );
+
+        let error = marshal_error::<Table1>(table([1, 3, 1, 1, 3], &[1, 2]));
+        assert!(
+            error.contains("should have 3 elements but has 4 bytes"),
+            "{error}"
+        );
+
+        let error = marshal_error::<Table2>(table([1, 2, 1, 1, 2], &[1, 2]));
+        assert!(
+            error.contains("expected a 2D table, got a 1D table"),
+            "{error}"
+        );
+
+        let error = marshal_error::<Table2>(table([2, 2, 2, 1, 3], &[1, 2, 3]));
+        assert!(
+            error.contains("table is 2x2x1 but has 3 elements"),
+            "{error}"
+        );
+
+        let error = marshal_error::<Table3>(table([3, u32::MAX, u32::MAX, u32::MAX, 1], &[1]));
+        assert!(error.contains("but has 1 elements"), "{error}");
+    }
+
+    #[test]
+    fn text_tables() {
+        let table: Table2 =
+            serde_json::from_str(r#"{"xsize": 2, "ysize": 1, "data": [1, 2]}"#).expect("valid");
+        assert_eq!(table.data, [1, 2]);
+
+        let error = serde_json::from_str::<Table1>(r#"{"xsize": 3, "data": [1, 2]}"#)
+            .expect_err("data is too short")
+            .to_string();
+        assert!(
+            error.contains("table is 3x1x1 but has 2 elements"),
+            "{error}"
+        );
+
+        let json = format!(r#"{{"xsize": {}, "ysize": 2, "data": []}}"#, usize::MAX);
+        let error = serde_json::from_str::<Table2>(&json)
+            .expect_err("sizes overflow")
+            .to_string();
+        assert!(error.contains("but has 0 elements"), "{error}");
+
+        let json = r#"{"xsize": 4294967296, "ysize": 0, "zsize": 1, "data": []}"#;
+        let error = serde_json::from_str::<Table3>(json)
+            .expect_err("too large for RGSS")
+            .to_string();
+        assert!(error.contains("too large for RGSS"), "{error}");
+    }
+}
@@ -0,0 +1,29 @@
+use crate::structured::Table2;
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Actor")]
+pub struct Actor {
+    pub id: usize,
+    pub name: String,
+    pub class_id: usize,
+    pub initial_level: i32,
+    pub exp_basis: i32,
+    pub exp_inflation: i32,
+    pub character_name: String,
+    pub character_index: i32,
+    pub face_name: String,
+    pub face_index: i32,
+    pub parameters: Table2,
+    pub weapon_id: usize,
+    pub armor1_id: usize,
+    pub armor2_id: usize,
+    pub armor3_id: usize,
+    pub armor4_id: usize,
+    pub two_swords_style: bool,
+    pub fix_equipment: bool,
+    pub auto_battle: bool,
+    pub super_guard: bool,
+    pub pharmacology: bool,
+    pub critical_bonus: bool,
+}
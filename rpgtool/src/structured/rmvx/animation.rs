@@ -0,0 +1,31 @@
+use crate::structured::{Color, Se};
+
+// Frames and the enums are unchanged from XP.
+pub use crate::structured::rmxp::animation::{Frame, Position, Scope};
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Animation")]
+pub struct Animation {
+    pub id: usize,
+    pub name: String,
+    pub animation1_name: String,
+    pub animation1_hue: i32,
+    pub animation2_name: String,
+    pub animation2_hue: i32,
+    pub position: Position,
+    pub frame_max: usize,
+    pub frames: Vec<Frame>,
+    pub timings: Vec<Timing>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Animation::Timing")]
+pub struct Timing {
+    pub frame: usize,
+    pub se: Se,
+    pub flash_scope: Scope,
+    pub flash_color: Color,
+    pub flash_duration: usize,
+}
@@ -0,0 +1,13 @@
+use crate::structured::Rect;
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Area")]
+pub struct Area {
+    pub id: usize,
+    pub name: String,
+    pub map_id: usize,
+    pub rect: Rect,
+    pub encounter_list: Vec<usize>,
+    pub order: i32,
+}
@@ -0,0 +1,42 @@
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Armor")]
+pub struct Armor {
+    // RPG::BaseItem
+    pub id: usize,
+    pub name: String,
+    pub icon_index: i32,
+    pub description: String,
+    pub note: String,
+    // RPG::Armor
+    pub kind: Kind,
+    pub price: i32,
+    pub eva: i32,
+    pub atk: i32,
+    pub def: i32,
+    pub spi: i32,
+    pub agi: i32,
+    pub prevent_critical: bool,
+    pub half_mp_cost: bool,
+    pub double_exp_gain: bool,
+    pub auto_hp_recover: bool,
+    pub element_set: Vec<usize>,
+    pub state_set: Vec<usize>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum Kind {
+    #[default]
+    Shield = 0,
+    Helmet = 1,
+    BodyArmor = 2,
+    Accessory = 3,
+}
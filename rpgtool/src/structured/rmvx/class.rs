@@ -0,0 +1,18 @@
+pub use crate::structured::Table1;
+pub use crate::structured::rmxp::class::{Learning, Position};
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Class")]
+pub struct Class {
+    pub id: usize,
+    pub name: String,
+    pub position: Position,
+    pub weapon_set: Vec<usize>,
+    pub armor_set: Vec<usize>,
+    pub element_ranks: Table1,
+    pub state_ranks: Table1,
+    pub learnings: Vec<Learning>,
+    pub skill_name_valid: bool,
+    pub skill_name: String,
+}
@@ -0,0 +1,122 @@
+pub use crate::structured::Table1;
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Enemy")]
+pub struct Enemy {
+    pub id: usize,
+    pub name: String,
+    pub battler_name: String,
+    pub battler_hue: i32,
+    pub maxhp: i32,
+    pub maxmp: i32,
+    pub atk: i32,
+    pub def: i32,
+    pub spi: i32,
+    pub agi: i32,
+    pub hit: i32,
+    pub eva: i32,
+    pub exp: i32,
+    pub gold: i32,
+    pub drop_item1: DropItem,
+    pub drop_item2: DropItem,
+    pub levitate: bool,
+    pub has_critical: bool,
+    pub element_ranks: Table1,
+    pub state_ranks: Table1,
+    pub actions: Vec<Action>,
+    pub note: String,
+}
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Enemy::DropItem")]
+pub struct DropItem {
+    pub kind: DropKind,
+    pub item_id: usize,
+    pub weapon_id: usize,
+    pub armor_id: usize,
+    pub denominator: i32,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Enemy::Action")]
+pub struct Action {
+    pub kind: Kind,
+    pub basic: Basic,
+    pub skill_id: usize,
+    pub condition_type: ConditionType,
+    pub condition_param1: i32,
+    pub condition_param2: i32,
+    pub rating: i32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum DropKind {
+    #[default]
+    None = 0,
+    Item = 1,
+    Weapon = 2,
+    Armor = 3,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum Kind {
+    #[default]
+    Basic = 0,
+    Skill = 1,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum Basic {
+    #[default]
+    Attack = 0,
+    Defend = 1,
+    Escape = 2,
+    DoNothing = 3,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum ConditionType {
+    #[default]
+    Always = 0,
+    Turn = 1,
+    Hp = 2,
+    Mp = 3,
+    State = 4,
+    PartyLevel = 5,
+    Switch = 6,
+}
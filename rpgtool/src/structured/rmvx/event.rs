@@ -0,0 +1,79 @@
+use crate::structured::{
+    EventCommand, EventTrigger, MoveFreq, MoveRoute, MoveSpeed, MoveType, SelfSwitch,
+};
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Event")]
+pub struct Event {
+    pub id: usize,
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub pages: Vec<EventPage>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Event::Page")]
+pub struct EventPage {
+    pub condition: EventCondition,
+    pub graphic: Graphic,
+    pub move_type: MoveType,
+    pub move_speed: MoveSpeed,
+    pub move_frequency: MoveFreq,
+    pub move_route: MoveRoute,
+    pub walk_anime: bool,
+    pub step_anime: bool,
+    pub direction_fix: bool,
+    pub through: bool,
+    pub priority_type: PriorityType,
+    pub trigger: EventTrigger,
+    pub list: Vec<EventCommand>,
+}
+
+/// RGSS2 added item and actor conditions.
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Event::Page::Condition")]
+pub struct EventCondition {
+    pub switch1_valid: bool,
+    pub switch2_valid: bool,
+    pub variable_valid: bool,
+    pub self_switch_valid: bool,
+    pub item_valid: bool,
+    pub actor_valid: bool,
+
+    pub switch1_id: usize,
+    pub switch2_id: usize,
+
+    pub variable_id: usize,
+    pub variable_value: i32,
+    pub self_switch_ch: SelfSwitch,
+    pub item_id: usize,
+    pub actor_id: usize,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Event::Page::Graphic")]
+pub struct Graphic {
+    pub tile_id: usize,
+    pub character_name: String,
+    pub character_index: i32,
+    pub direction: i32,
+    pub pattern: i32,
+}
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[serde(try_from = "u8", into = "u8")]
+#[marshal(try_from = "u8", into = "u8")]
+#[repr(u8)]
+pub enum PriorityType {
+    Below,
+    Same,
+    Above,
+}
@@ -0,0 +1,60 @@
+pub use crate::structured::Occasion;
+pub use crate::structured::rmvx::Scope;
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Item")]
+pub struct Item {
+    // RPG::BaseItem
+    pub id: usize,
+    pub name: String,
+    pub icon_index: i32,
+    pub description: String,
+    pub note: String,
+    // RPG::UsableItem
+    pub scope: Scope,
+    pub occasion: Occasion,
+    pub speed: i32,
+    pub animation_id: usize,
+    pub common_event_id: usize,
+    pub base_damage: i32,
+    pub variance: i32,
+    pub atk_f: i32,
+    pub spi_f: i32,
+    pub physical_attack: bool,
+    pub damage_to_mp: bool,
+    pub absorb_damage: bool,
+    pub ignore_defense: bool,
+    pub element_set: Vec<usize>,
+    pub plus_state_set: Vec<usize>,
+    pub minus_state_set: Vec<usize>,
+    // RPG::Item
+    pub price: i32,
+    pub consumable: bool,
+    pub hp_recovery_rate: i32,
+    pub hp_recovery: i32,
+    pub mp_recovery_rate: i32,
+    pub mp_recovery: i32,
+    pub parameter_type: ParameterType,
+    pub parameter_points: i32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum ParameterType {
+    #[default]
+    None = 0,
+    MaxHP = 1,
+    MaxMP = 2,
+    Atk = 3,
+    Def = 4,
+    Spi = 5,
+    Agi = 6,
+}
@@ -0,0 +1,42 @@
+use crate::structured::{Bgm, Bgs, Table3, rmvx::Event};
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Map")]
+pub struct Map {
+    pub width: usize,
+    pub height: usize,
+    pub scroll_type: ScrollType,
+    pub autoplay_bgm: bool,
+    pub bgm: Bgm,
+    pub autoplay_bgs: bool,
+    pub bgs: Bgs,
+    pub disable_dashing: bool,
+    pub encounter_list: Vec<usize>,
+    pub encounter_step: i32,
+    pub parallax_name: String,
+    pub parallax_loop_x: bool,
+    pub parallax_loop_y: bool,
+    pub parallax_sx: i32,
+    pub parallax_sy: i32,
+    pub parallax_show: bool,
+    pub data: Table3,
+    pub events: std::collections::BTreeMap<usize, Event>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum ScrollType {
+    #[default]
+    NoLoop = 0,
+    LoopVertically = 1,
+    LoopHorizontally = 2,
+    LoopBoth = 3,
+}
@@ -0,0 +1,68 @@
+pub mod actor;
+pub mod animation;
+pub mod area;
+pub mod armor;
+pub mod class;
+pub mod enemy;
+pub mod event;
+pub mod item;
+pub mod map;
+pub mod skill;
+pub mod state;
+pub mod system;
+pub mod troop;
+pub mod weapon;
+
+pub use actor::Actor;
+pub use animation::Animation;
+pub use area::Area;
+pub use armor::Armor;
+pub use class::Class;
+pub use enemy::Enemy;
+pub use event::{Event, EventCondition, EventPage, Graphic};
+pub use item::Item;
+pub use map::Map;
+pub use skill::Skill;
+pub use state::State;
+pub use system::System;
+pub use troop::Troop;
+pub use weapon::Weapon;
+
+use crate::structured::NilPadded;
+
+pub type Actors = NilPadded<Actor>;
+pub type Animations = NilPadded<Animation>;
+pub type Areas = std::collections::BTreeMap<usize, Area>;
+pub type Armors = NilPadded<Armor>;
+pub type Classes = NilPadded<Class>;
+pub type Enemies = NilPadded<Enemy>;
+pub type Items = NilPadded<Item>;
+pub type Skills = NilPadded<Skill>;
+pub type States = NilPadded<State>;
+pub type Troops = NilPadded<Troop>;
+pub type Weapons = NilPadded<Weapon>;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum Scope {
+    #[default]
+    None = 0,
+    OneEnemy = 1,
+    AllEnemies = 2,
+    OneEnemyDual = 3,
+    OneRandomEnemy = 4,
+    TwoRandomEnemies = 5,
+    ThreeRandomEnemies = 6,
+    OneAlly = 7,
+    AllAllies = 8,
+    OneAllyHP0 = 9,
+    AllAlliesHP0 = 10,
+    User = 11,
+}
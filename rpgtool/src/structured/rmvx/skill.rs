@@ -0,0 +1,36 @@
+pub use crate::structured::Occasion;
+pub use crate::structured::rmvx::Scope;
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Skill")]
+pub struct Skill {
+    // RPG::BaseItem
+    pub id: usize,
+    pub name: String,
+    pub icon_index: i32,
+    pub description: String,
+    pub note: String,
+    // RPG::UsableItem
+    pub scope: Scope,
+    pub occasion: Occasion,
+    pub speed: i32,
+    pub animation_id: usize,
+    pub common_event_id: usize,
+    pub base_damage: i32,
+    pub variance: i32,
+    pub atk_f: i32,
+    pub spi_f: i32,
+    pub physical_attack: bool,
+    pub damage_to_mp: bool,
+    pub absorb_damage: bool,
+    pub ignore_defense: bool,
+    pub element_set: Vec<usize>,
+    pub plus_state_set: Vec<usize>,
+    pub minus_state_set: Vec<usize>,
+    // RPG::Skill
+    pub mp_cost: i32,
+    pub hit: i32,
+    pub message1: String,
+    pub message2: String,
+}
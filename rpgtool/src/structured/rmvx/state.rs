@@ -0,0 +1,48 @@
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::State")]
+pub struct State {
+    pub id: usize,
+    pub name: String,
+    pub icon_index: i32,
+    pub restriction: Restriction,
+    pub priority: i32,
+    pub atk_rate: i32,
+    pub def_rate: i32,
+    pub spi_rate: i32,
+    pub agi_rate: i32,
+    pub nonresistance: bool,
+    pub offset_by_opposite: bool,
+    pub slip_damage: bool,
+    pub reduce_hit_ratio: bool,
+    pub battle_only: bool,
+    pub release_by_damage: bool,
+    pub hold_turn: i32,
+    pub auto_release_prob: i32,
+    pub message1: String,
+    pub message2: String,
+    pub message3: String,
+    pub message4: String,
+    pub element_set: Vec<usize>,
+    pub state_set: Vec<usize>,
+    pub note: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum Restriction {
+    #[default]
+    None = 0,
+    NoMagic = 1,
+    AttackEnemies = 2,
+    AttackAllies = 3,
+    NoMove = 4,
+    NoMoveNoEvade = 5,
+}
@@ -0,0 +1,99 @@
+pub use crate::structured::{Bgm, Me, Se};
+use crate::structured::{NilPadded, Table1};
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::System")]
+pub struct System {
+    pub game_title: String,
+    pub version_id: i32,
+    pub party_members: Vec<usize>,
+    pub elements: Vec<Option<String>>, // may or may not be nil padded depending on the editor version
+    pub switches: NilPadded<String>,
+    pub variables: NilPadded<String>,
+    pub passages: Table1,
+    pub boat: Vehicle,
+    pub ship: Vehicle,
+    pub airship: Vehicle,
+    pub title_bgm: Bgm,
+    pub battle_bgm: Bgm,
+    pub battle_end_me: Me,
+    pub gameover_me: Me,
+    pub sounds: Vec<Se>,
+    pub test_battlers: Vec<TestBattler>,
+    pub test_troop_id: usize,
+    pub start_map_id: usize,
+    pub start_x: i32,
+    pub start_y: i32,
+    pub terms: Terms,
+    pub battler_name: String,
+    pub battler_hue: i32,
+    pub edit_map_id: usize,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::System::Vehicle")]
+pub struct Vehicle {
+    pub character_name: String,
+    pub character_index: i32,
+    pub bgm: Bgm,
+    pub start_map_id: usize,
+    pub start_x: i32,
+    pub start_y: i32,
+}
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::System::Terms")]
+#[serde(default)]
+pub struct Terms {
+    pub level: String,
+    pub level_a: String,
+    pub hp: String,
+    pub hp_a: String,
+    pub mp: String,
+    pub mp_a: String,
+    pub atk: String,
+    pub def: String,
+    pub spi: String,
+    pub agi: String,
+    pub weapon: String,
+    pub armor1: String,
+    pub armor2: String,
+    pub armor3: String,
+    pub armor4: String,
+    pub weapon1: String,
+    pub weapon2: String,
+    pub attack: String,
+    pub skill: String,
+    pub guard: String,
+    pub item: String,
+    pub equip: String,
+    pub status: String,
+    pub save: String,
+    pub game_end: String,
+    pub fight: String,
+    pub escape: String,
+    pub new_game: String,
+    #[marshal(rename = "continue")]
+    #[serde(rename = "continue")]
+    pub continue_: String,
+    pub shutdown: String,
+    pub to_title: String,
+    pub cancel: String,
+    pub gold: String,
+}
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::System::TestBattler")]
+pub struct TestBattler {
+    pub actor_id: usize,
+    pub level: i32,
+    pub weapon_id: usize,
+    pub armor1_id: usize,
+    pub armor2_id: usize,
+    pub armor3_id: usize,
+    pub armor4_id: usize,
+}
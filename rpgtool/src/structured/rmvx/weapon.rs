@@ -0,0 +1,25 @@
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Weapon")]
+pub struct Weapon {
+    // RPG::BaseItem
+    pub id: usize,
+    pub name: String,
+    pub icon_index: i32,
+    pub description: String,
+    pub note: String,
+    // RPG::Weapon
+    pub animation_id: usize,
+    pub price: i32,
+    pub hit: i32,
+    pub atk: i32,
+    pub def: i32,
+    pub spi: i32,
+    pub agi: i32,
+    pub two_handed: bool,
+    pub fast_attack: bool,
+    pub dual_attack: bool,
+    pub critical_bonus: bool,
+    pub element_set: Vec<usize>,
+    pub state_set: Vec<usize>,
+}
@@ -0,0 +1,24 @@
+use crate::structured::rmvxa::Feature;
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Actor")]
+pub struct Actor {
+    // RPG::BaseItem
+    pub id: usize,
+    pub name: String,
+    pub icon_index: i32,
+    pub description: String,
+    pub features: Vec<Feature>,
+    pub note: String,
+    // RPG::Actor
+    pub nickname: String,
+    pub class_id: usize,
+    pub initial_level: i32,
+    pub max_level: i32,
+    pub character_name: String,
+    pub character_index: i32,
+    pub face_name: String,
+    pub face_index: i32,
+    pub equips: [usize; 5],
+}
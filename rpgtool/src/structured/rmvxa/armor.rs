@@ -0,0 +1,20 @@
+use crate::structured::rmvxa::Feature;
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Armor")]
+pub struct Armor {
+    // RPG::BaseItem
+    pub id: usize,
+    pub name: String,
+    pub icon_index: i32,
+    pub description: String,
+    pub features: Vec<Feature>,
+    pub note: String,
+    // RPG::EquipItem
+    pub price: i32,
+    pub etype_id: usize,
+    pub params: [i32; 8],
+    // RPG::Armor
+    pub atype_id: usize,
+}
@@ -0,0 +1,27 @@
+use crate::structured::{Table2, rmvxa::Feature};
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Class")]
+pub struct Class {
+    // RPG::BaseItem
+    pub id: usize,
+    pub name: String,
+    pub icon_index: i32,
+    pub description: String,
+    pub features: Vec<Feature>,
+    pub note: String,
+    // RPG::Class
+    pub exp_params: [i32; 4],
+    pub params: Table2,
+    pub learnings: Vec<Learning>,
+}
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Class::Learning")]
+pub struct Learning {
+    pub level: i32,
+    pub skill_id: usize,
+    pub note: String,
+}
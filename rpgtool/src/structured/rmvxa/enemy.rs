@@ -0,0 +1,79 @@
+use crate::structured::rmvxa::{Feature, Number};
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Enemy")]
+pub struct Enemy {
+    // RPG::BaseItem
+    pub id: usize,
+    pub name: String,
+    pub icon_index: i32,
+    pub description: String,
+    pub features: Vec<Feature>,
+    pub note: String,
+    // RPG::Enemy
+    pub battler_name: String,
+    pub battler_hue: i32,
+    pub params: [i32; 8],
+    pub exp: i32,
+    pub gold: i32,
+    pub drop_items: [DropItem; 3],
+    pub actions: Vec<Action>,
+}
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Enemy::DropItem")]
+pub struct DropItem {
+    pub kind: DropKind,
+    pub data_id: usize,
+    pub denominator: i32,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Enemy::Action")]
+pub struct Action {
+    pub skill_id: usize,
+    pub condition_type: ConditionType,
+    pub condition_param1: Number,
+    pub condition_param2: Number,
+    pub rating: i32,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum DropKind {
+    #[default]
+    None = 0,
+    Item = 1,
+    Weapon = 2,
+    Armor = 3,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum ConditionType {
+    #[default]
+    Always = 0,
+    Turn = 1,
+    Hp = 2,
+    Mp = 3,
+    State = 4,
+    PartyLevel = 5,
+    Switch = 6,
+}
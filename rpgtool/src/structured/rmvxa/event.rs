@@ -0,0 +1,34 @@
+use crate::structured::{
+    EventCommand, EventTrigger, MoveFreq, MoveRoute, MoveSpeed, MoveType,
+    rmvx::{EventCondition, Graphic, event::PriorityType},
+};
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Event")]
+pub struct Event {
+    pub id: usize,
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub pages: Vec<EventPage>,
+}
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Event::Page")]
+pub struct EventPage {
+    pub condition: EventCondition,
+    pub graphic: Graphic,
+    pub move_type: MoveType,
+    pub move_speed: MoveSpeed,
+    pub move_frequency: MoveFreq,
+    pub move_route: MoveRoute,
+    pub walk_anime: bool,
+    pub step_anime: bool,
+    pub direction_fix: bool,
+    pub through: bool,
+    pub priority_type: PriorityType,
+    pub trigger: EventTrigger,
+    pub list: Vec<EventCommand>,
+}
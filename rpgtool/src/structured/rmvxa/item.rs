@@ -0,0 +1,31 @@
+pub use crate::structured::Occasion;
+pub use crate::structured::rmvx::Scope;
+use crate::structured::rmvxa::{Damage, Effect, Feature, usable_item::HitType};
+
+#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Item")]
+pub struct Item {
+    // RPG::BaseItem
+    pub id: usize,
+    pub name: String,
+    pub icon_index: i32,
+    pub description: String,
+    pub features: Vec<Feature>,
+    pub note: String,
+    // RPG::UsableItem
+    pub scope: Scope,
+    pub occasion: Occasion,
+    pub speed: i32,
+    pub success_rate: i32,
+    pub repeats: i32,
+    pub tp_gain: i32,
+    pub hit_type: HitType,
+    pub animation_id: i32,
+    pub damage: Damage,
+    pub effects: Vec<Effect>,
+    // RPG::Item
+    pub itype_id: usize,
+    pub price: i32,
+    pub consumable: bool,
+}
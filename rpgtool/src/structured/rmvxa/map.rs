@@ -0,0 +1,40 @@
+use crate::structured::{Bgm, Bgs, Table3, rmvx::map::ScrollType, rmvxa::Event};
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Map")]
+pub struct Map {
+    pub display_name: String,
+    pub tileset_id: usize,
+    pub width: usize,
+    pub height: usize,
+    pub scroll_type: ScrollType,
+    pub specify_battleback: bool,
+    pub battleback1_name: String,
+    pub battleback2_name: String,
+    pub autoplay_bgm: bool,
+    pub bgm: Bgm,
+    pub autoplay_bgs: bool,
+    pub bgs: Bgs,
+    pub disable_dashing: bool,
+    pub encounter_list: Vec<Encounter>,
+    pub encounter_step: i32,
+    pub parallax_name: String,
+    pub parallax_loop_x: bool,
+    pub parallax_loop_y: bool,
+    pub parallax_sx: i32,
+    pub parallax_sy: i32,
+    pub parallax_show: bool,
+    pub note: String,
+    pub data: Table3,
+    pub events: std::collections::BTreeMap<usize, Event>,
+}
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Map::Encounter")]
+pub struct Encounter {
+    pub troop_id: usize,
+    pub weight: i32,
+    pub region_set: Vec<usize>,
+}
@@ -0,0 +1,97 @@
+pub mod actor;
+pub mod armor;
+pub mod class;
+pub mod enemy;
+pub mod event;
+pub mod item;
+pub mod map;
+pub mod skill;
+pub mod state;
+pub mod system;
+pub mod tileset;
+pub mod troop;
+pub mod usable_item;
+pub mod weapon;
+
+pub use actor::Actor;
+pub use armor::Armor;
+pub use class::Class;
+pub use enemy::Enemy;
+pub use event::{Event, EventPage};
+pub use item::Item;
+pub use map::Map;
+pub use skill::Skill;
+pub use state::State;
+pub use system::System;
+pub use tileset::Tileset;
+pub use troop::Troop;
+pub use usable_item::{Damage, Effect};
+pub use weapon::Weapon;
+
+// Animations and event page conditions are unchanged from VX.
+pub use crate::structured::rmvx::{Animation, EventCondition};
+
+use crate::structured::NilPadded;
+
+pub type Actors = NilPadded<Actor>;
+pub type Animations = NilPadded<Animation>;
+pub type Armors = NilPadded<Armor>;
+pub type Classes = NilPadded<Class>;
+pub type Enemies = NilPadded<Enemy>;
+pub type Items = NilPadded<Item>;
+pub type Skills = NilPadded<Skill>;
+pub type States = NilPadded<State>;
+pub type Tilesets = NilPadded<Tileset>;
+pub type Troops = NilPadded<Troop>;
+pub type Weapons = NilPadded<Weapon>;
+
+#[derive(Default, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::BaseItem::Feature")]
+pub struct Feature {
+    pub code: u8,
+    pub data_id: usize,
+    pub value: Number,
+}
+
+/// The editor is inconsistent about storing some values as integers or floats.
+///
+/// This preserves whichever one was used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(try_from = "alox_48::Value", into = "alox_48::Value")]
+#[serde(untagged)]
+pub enum Number {
+    Integer(i32),
+    Float(f64),
+}
+
+impl Default for Number {
+    fn default() -> Self {
+        Self::Integer(0)
+    }
+}
+
+impl TryFrom<alox_48::Value> for Number {
+    type Error = alox_48::DeError;
+
+    fn try_from(value: alox_48::Value) -> Result<Self, Self::Error> {
+        match value {
+            alox_48::Value::Integer(v) => Ok(Self::Integer(v)),
+            alox_48::Value::Float(v) => Ok(Self::Float(v)),
+            _ => Err(alox_48::DeError::custom(format!(
+                "expected an integer or float, got {value:#?}"
+            ))),
+        }
+    }
+}
+
+impl From<Number> for alox_48::Value {
+    fn from(value: Number) -> Self {
+        match value {
+            Number::Integer(v) => Self::Integer(v),
+            Number::Float(v) => Self::Float(v),
+        }
+    }
+}
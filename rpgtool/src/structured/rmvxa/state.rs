@@ -0,0 +1,64 @@
+use crate::structured::rmvxa::Feature;
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::State")]
+pub struct State {
+    // RPG::BaseItem
+    pub id: usize,
+    pub name: String,
+    pub icon_index: i32,
+    pub description: String,
+    pub features: Vec<Feature>,
+    pub note: String,
+    // RPG::State
+    pub restriction: Restriction,
+    pub priority: i32,
+    pub remove_at_battle_end: bool,
+    pub remove_by_restriction: bool,
+    pub auto_removal_timing: AutoRemovalTiming,
+    pub min_turns: i32,
+    pub max_turns: i32,
+    pub remove_by_damage: bool,
+    pub chance_by_damage: i32,
+    pub remove_by_walking: bool,
+    pub steps_to_remove: i32,
+    pub message1: String,
+    pub message2: String,
+    pub message3: String,
+    pub message4: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum Restriction {
+    #[default]
+    None = 0,
+    AttackEnemies = 1,
+    AttackAnyone = 2,
+    AttackAllies = 3,
+    CannotMove = 4,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum AutoRemovalTiming {
+    #[default]
+    None = 0,
+    ActionEnd = 1,
+    TurnEnd = 2,
+}
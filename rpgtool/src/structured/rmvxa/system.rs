@@ -0,0 +1,71 @@
+use crate::structured::NilPadded;
+pub use crate::structured::{Bgm, Me, Se, Tone};
+
+// Vehicles are unchanged from VX.
+pub use crate::structured::rmvx::system::Vehicle;
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::System")]
+pub struct System {
+    pub game_title: String,
+    pub version_id: i32,
+    pub japanese: bool,
+    pub party_members: Vec<usize>,
+    pub currency_unit: String,
+    pub elements: NilPadded<String>,
+    pub skill_types: NilPadded<String>,
+    pub weapon_types: NilPadded<String>,
+    pub armor_types: NilPadded<String>,
+    pub switches: NilPadded<String>,
+    pub variables: NilPadded<String>,
+    pub boat: Vehicle,
+    pub ship: Vehicle,
+    pub airship: Vehicle,
+    pub title1_name: String,
+    pub title2_name: String,
+    pub opt_draw_title: bool,
+    pub opt_use_midi: bool,
+    pub opt_transparent: bool,
+    pub opt_followers: bool,
+    pub opt_slip_death: bool,
+    pub opt_floor_death: bool,
+    pub opt_display_tp: bool,
+    pub opt_extra_exp: bool,
+    pub window_tone: Tone,
+    pub title_bgm: Bgm,
+    pub battle_bgm: Bgm,
+    pub battle_end_me: Me,
+    pub gameover_me: Me,
+    pub sounds: Vec<Se>,
+    pub test_battlers: Vec<TestBattler>,
+    pub test_troop_id: usize,
+    pub start_map_id: usize,
+    pub start_x: i32,
+    pub start_y: i32,
+    pub terms: Terms,
+    pub battleback1_name: String,
+    pub battleback2_name: String,
+    pub battler_name: String,
+    pub battler_hue: i32,
+    pub edit_map_id: usize,
+}
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::System::Terms")]
+pub struct Terms {
+    pub basic: Vec<String>,
+    pub params: Vec<String>,
+    pub etypes: Vec<String>,
+    pub commands: Vec<String>,
+}
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::System::TestBattler")]
+pub struct TestBattler {
+    pub actor_id: usize,
+    pub level: i32,
+    pub equips: Vec<usize>,
+}
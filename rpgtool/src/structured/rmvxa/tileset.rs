@@ -0,0 +1,29 @@
+use crate::structured::Table1;
+
+#[derive(Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Tileset")]
+pub struct Tileset {
+    pub id: usize,
+    pub mode: Mode,
+    pub name: String,
+    pub tileset_names: [String; 9],
+    pub flags: Table1,
+    pub note: String,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum Mode {
+    Field = 0,
+    #[default]
+    Area = 1,
+    Vx = 2,
+}
@@ -0,0 +1,22 @@
+// Pages are unchanged from VX.
+pub use crate::structured::rmvx::troop::{Condition, Page};
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Troop")]
+pub struct Troop {
+    pub id: usize,
+    pub name: String,
+    pub members: Vec<Member>,
+    pub pages: Vec<Page>,
+}
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Troop::Member")]
+pub struct Member {
+    pub enemy_id: usize,
+    pub x: i32,
+    pub y: i32,
+    pub hidden: bool,
+}
@@ -0,0 +1,60 @@
+use crate::structured::rmvxa::Number;
+
+#[derive(Default, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::UsableItem::Damage")]
+pub struct Damage {
+    #[marshal(rename = "type")]
+    #[serde(rename = "type")]
+    pub kind: DamageKind,
+    pub element_id: i32,
+    pub formula: String,
+    pub variance: i32,
+    pub critical: bool,
+}
+
+#[derive(Default, Debug, Clone, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::UsableItem::Effect")]
+pub struct Effect {
+    pub code: u8,
+    pub data_id: usize,
+    pub value1: Number,
+    pub value2: Number,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum DamageKind {
+    #[default]
+    None = 0,
+    HpDamage = 1,
+    MpDamage = 2,
+    HpRecover = 3,
+    MpRecover = 4,
+    HpDrain = 5,
+    MpDrain = 6,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Default)]
+#[derive(num_enum::TryFromPrimitive, num_enum::IntoPrimitive)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[repr(u8)]
+#[serde(into = "u8")]
+#[serde(try_from = "u8")]
+#[marshal(into = "u8")]
+#[marshal(try_from = "u8")]
+pub enum HitType {
+    #[default]
+    Certain = 0,
+    Physical = 1,
+    Magical = 2,
+}
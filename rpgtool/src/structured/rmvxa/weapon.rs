@@ -0,0 +1,21 @@
+use crate::structured::rmvxa::Feature;
+
+#[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::Weapon")]
+pub struct Weapon {
+    // RPG::BaseItem
+    pub id: usize,
+    pub name: String,
+    pub icon_index: i32,
+    pub description: String,
+    pub features: Vec<Feature>,
+    pub note: String,
+    // RPG::EquipItem
+    pub price: i32,
+    pub etype_id: usize,
+    pub params: [i32; 8],
+    // RPG::Weapon
+    pub wtype_id: usize,
+    pub animation_id: usize,
+}
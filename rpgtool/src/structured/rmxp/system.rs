@@ -31,9 +31,7 @@ pub struct System {
     pub actor_collapse_se: AudioFile,
     pub enemy_collapse_se: AudioFile,
     pub words: Words,
-    #[serde(skip)]
-    // #[marshal(skip)]
-    pub test_battlers: alox_48::Value,
+    pub test_battlers: Vec<TestBattler>,
     pub test_troop_id: usize,
     pub start_map_id: usize,
     pub start_x: i32,
@@ -74,12 +72,13 @@ pub struct Words {
 #[derive(Default, Debug, serde::Deserialize, serde::Serialize)]
 #[derive(alox_48::Deserialize, alox_48::Serialize)]
 #[marshal(class = "RPG::System::TestBattler")]
+// fields are in the order RGSS1's `TestBattler#initialize` sets them, which is the order they're marshaled in
 pub struct TestBattler {
-    level: i32,
-    actor_id: usize,
-    weapon_id: usize,
-    armor1_id: usize,
-    armor2_id: usize,
-    armor3_id: usize,
-    armor4_id: usize,
+    pub actor_id: usize,
+    pub level: i32,
+    pub weapon_id: usize,
+    pub armor1_id: usize,
+    pub armor2_id: usize,
+    pub armor3_id: usize,
+    pub armor4_id: usize,
 }
@@ -9,3 +9,46 @@ pub struct AudioFile {
     pub volume: u8,
     pub pitch: u8,
 }
+
+// RGSS2 onwards subclasses RPG::AudioFile for each kind of audio.
+// They share a layout, only the class name differs.
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::BGM")]
+pub struct Bgm {
+    pub name: String,
+    pub volume: u8,
+    pub pitch: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::BGS")]
+pub struct Bgs {
+    pub name: String,
+    pub volume: u8,
+    pub pitch: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::ME")]
+pub struct Me {
+    pub name: String,
+    pub volume: u8,
+    pub pitch: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[derive(serde::Deserialize, serde::Serialize)]
+#[derive(alox_48::Deserialize, alox_48::Serialize)]
+#[marshal(class = "RPG::SE")]
+pub struct Se {
+    pub name: String,
+    pub volume: u8,
+    pub pitch: u8,
+}
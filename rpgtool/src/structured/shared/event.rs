@@ -1,4 +1,7 @@
-use crate::structured::{BlendMode, MoveRoute, ParameterType};
+use crate::structured::{
+    AudioFile, BlendMode, Color, MoveCommand, MoveRoute, ParameterType, Tone, typed_commands,
+    typed_event_commands,
+};
 
 #[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
 #[derive(alox_48::Deserialize, alox_48::Serialize)]
@@ -136,8 +139,8 @@ pub struct EventCondition {
 #[derive(serde::Deserialize, serde::Serialize)]
 #[derive(alox_48::Deserialize, alox_48::Serialize)]
 #[derive(Debug, Clone, Copy, PartialEq)]
-#[serde(from = "String", into = "String")]
-#[marshal(from = "String", into = "String")]
+#[serde(try_from = "String", into = "String")]
+#[marshal(try_from = "String", into = "String")]
 pub enum SelfSwitch {
     A,
     B,
@@ -145,14 +148,18 @@ pub enum SelfSwitch {
     D,
 }
 
-impl From<String> for SelfSwitch {
-    fn from(value: String) -> Self {
+impl TryFrom<String> for SelfSwitch {
+    type Error = alox_48::DeError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
         match value.as_str() {
-            "A" => Self::A,
-            "B" => Self::B,
-            "C" => Self::C,
-            "D" => Self::D,
-            _ => panic!("wrong value for self switch"),
+            "A" => Ok(Self::A),
+            "B" => Ok(Self::B),
+            "C" => Ok(Self::C),
+            "D" => Ok(Self::D),
+            _ => Err(alox_48::DeError::custom(format!(
+                "invalid self switch {value:?}, expected one of A, B, C, D"
+            ))),
         }
     }
 }
@@ -168,7 +175,8 @@ impl From<SelfSwitch> for String {
     }
 }
 
-#[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
+/// Serialized typed or raw depending on [`set_typed_event_commands`](crate::structured::set_typed_event_commands).
+#[derive(Debug, Clone)]
 #[derive(alox_48::Deserialize, alox_48::Serialize)]
 #[allow(missing_docs)]
 #[marshal(class = "RPG::EventCommand")]
@@ -177,3 +185,207 @@ pub struct EventCommand {
     pub indent: usize,
     pub parameters: Vec<ParameterType>,
 }
+
+impl serde::Serialize for EventCommand {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        if typed_event_commands() {
+            let mut s = serializer.serialize_struct("EventCommand", 2)?;
+            s.serialize_field("indent", &self.indent)?;
+            let command = TypedEventCommand::from_raw(self.code, &self.parameters);
+            s.serialize_field("command", &command)?;
+            s.end()
+        } else {
+            let mut s = serializer.serialize_struct("EventCommand", 3)?;
+            s.serialize_field("code", &self.code)?;
+            s.serialize_field("indent", &self.indent)?;
+            s.serialize_field("parameters", &self.parameters)?;
+            s.end()
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for EventCommand {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = EventCommand;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("a raw or typed event command")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                use serde::de::Error;
+
+                let mut code = None;
+                let mut indent = None;
+                let mut parameters = None;
+                let mut command = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "code" => code = Some(map.next_value()?),
+                        "indent" => indent = Some(map.next_value()?),
+                        "parameters" => parameters = Some(map.next_value()?),
+                        "command" => command = Some(map.next_value::<TypedEventCommand>()?),
+                        _ => {
+                            map.next_value::<serde::de::IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                let indent = indent.ok_or_else(|| A::Error::missing_field("indent"))?;
+                let (code, parameters) = match (command, code, parameters) {
+                    (Some(command), None, None) => command.to_raw(),
+                    (None, Some(code), Some(parameters)) => (code, parameters),
+                    (Some(_), ..) => {
+                        return Err(A::Error::custom(
+                            "a typed command can't also have a code or parameters",
+                        ));
+                    }
+                    (None, None, _) => return Err(A::Error::missing_field("code")),
+                    (None, _, None) => return Err(A::Error::missing_field("parameters")),
+                };
+
+                Ok(EventCommand {
+                    code,
+                    indent,
+                    parameters,
+                })
+            }
+        }
+
+        const FIELDS: &[&str] = &["code", "indent", "parameters", "command"];
+        deserializer.deserialize_struct("EventCommand", FIELDS, Visitor)
+    }
+}
+
+typed_commands! {
+    /// RGSS1 event commands.
+    ///
+    /// Field types follow what the RPG Maker XP editor writes.
+    /// Most "operand type" style fields are 0 for a constant and 1 for a variable.
+    pub enum TypedEventCommand {
+        0 => Empty {},
+        101 => ShowText { text: String },
+        401 => ShowTextContinued { text: String },
+        102 => ShowChoices { choices: Vec<String>, cancel_type: i32 },
+        402 => WhenChoice { index: i32, text: String },
+        403 => WhenCancel {},
+        404 => ChoicesEnd {},
+        103 => InputNumber { variable_id: i32, digits: i32 },
+        104 => ChangeTextOptions { position: i32, frame: i32 },
+        105 => ButtonInputProcessing { variable_id: i32 },
+        106 => Wait { frames: i32 },
+        108 => Comment { text: String },
+        408 => CommentContinued { text: String },
+        /// The remaining parameters depend on `kind`.
+        111 => ConditionalBranch { kind: i32, ..args },
+        411 => Else {},
+        412 => BranchEnd {},
+        112 => Loop {},
+        413 => RepeatAbove {},
+        113 => BreakLoop {},
+        115 => ExitEventProcessing {},
+        116 => EraseEvent {},
+        117 => CallCommonEvent { common_event_id: i32 },
+        118 => Label { name: String },
+        119 => JumpToLabel { name: String },
+        /// `value` is 0 for ON and 1 for OFF.
+        121 => ControlSwitches { start_id: i32, end_id: i32, value: i32 },
+        /// The remaining parameters depend on `operand_type`.
+        122 => ControlVariables { start_id: i32, end_id: i32, operation: i32, operand_type: i32, ..operand },
+        123 => ControlSelfSwitch { self_switch: String, value: i32 },
+        124 => ControlTimer { operation: i32, ..args },
+        125 => ChangeGold { operation: i32, operand_type: i32, operand: i32 },
+        126 => ChangeItems { item_id: i32, operation: i32, operand_type: i32, operand: i32 },
+        127 => ChangeWeapons { weapon_id: i32, operation: i32, operand_type: i32, operand: i32 },
+        128 => ChangeArmor { armor_id: i32, operation: i32, operand_type: i32, operand: i32 },
+        129 => ChangePartyMember { actor_id: i32, operation: i32, initialize: i32 },
+        131 => ChangeWindowskin { name: String },
+        132 => ChangeBattleBgm { bgm: AudioFile },
+        133 => ChangeBattleEndMe { me: AudioFile },
+        134 => ChangeSaveAccess { disabled: i32 },
+        135 => ChangeMenuAccess { disabled: i32 },
+        136 => ChangeEncounter { disabled: i32 },
+        201 => TransferPlayer { designation: i32, map_id: i32, x: i32, y: i32, direction: i32, fading: i32 },
+        202 => SetEventLocation { event_id: i32, designation: i32, x: i32, y: i32, direction: i32 },
+        203 => ScrollMap { direction: i32, distance: i32, speed: i32 },
+        /// The remaining parameters depend on `kind`.
+        204 => ChangeMapSettings { kind: i32, ..args },
+        205 => ChangeFogColorTone { tone: Tone, duration: i32 },
+        206 => ChangeFogOpacity { opacity: i32, duration: i32 },
+        207 => ShowAnimation { event_id: i32, animation_id: i32 },
+        208 => ChangeTransparentFlag { transparent: i32 },
+        209 => SetMoveRoute { event_id: i32, move_route: MoveRoute },
+        509 => SetMoveRouteContinued { command: MoveCommand },
+        210 => WaitForMoveCompletion {},
+        221 => PrepareForTransition {},
+        222 => ExecuteTransition { name: String },
+        223 => ChangeScreenColorTone { tone: Tone, duration: i32 },
+        224 => ScreenFlash { color: Color, duration: i32 },
+        225 => ScreenShake { power: i32, speed: i32, duration: i32 },
+        231 => ShowPicture { number: i32, name: String, origin: i32, designation: i32, x: i32, y: i32, zoom_x: i32, zoom_y: i32, opacity: i32, blend_type: i32 },
+        232 => MovePicture { number: i32, duration: i32, origin: i32, designation: i32, x: i32, y: i32, zoom_x: i32, zoom_y: i32, opacity: i32, blend_type: i32 },
+        233 => RotatePicture { number: i32, speed: i32 },
+        234 => ChangePictureColorTone { number: i32, tone: Tone, duration: i32 },
+        235 => ErasePicture { number: i32 },
+        236 => SetWeatherEffects { kind: i32, power: i32, duration: i32 },
+        241 => PlayBgm { bgm: AudioFile },
+        242 => FadeOutBgm { seconds: i32 },
+        245 => PlayBgs { bgs: AudioFile },
+        246 => FadeOutBgs { seconds: i32 },
+        247 => MemorizeBgmBgs {},
+        248 => RestoreBgmBgs {},
+        249 => PlayMe { me: AudioFile },
+        250 => PlaySe { se: AudioFile },
+        251 => StopSe {},
+        301 => BattleProcessing { troop_id: i32, can_escape: bool, can_lose: bool },
+        601 => IfWin {},
+        602 => IfEscape {},
+        603 => IfLose {},
+        604 => BattleBranchEnd {},
+        302 => ShopProcessing { kind: i32, id: i32 },
+        605 => ShopItem { kind: i32, id: i32 },
+        303 => NameInputProcessing { actor_id: i32, max_characters: i32 },
+        311 => ChangeHp { actor_id: i32, operation: i32, operand_type: i32, operand: i32, allow_knockout: bool },
+        312 => ChangeSp { actor_id: i32, operation: i32, operand_type: i32, operand: i32 },
+        313 => ChangeState { actor_id: i32, operation: i32, state_id: i32 },
+        314 => RecoverAll { actor_id: i32 },
+        315 => ChangeExp { actor_id: i32, operation: i32, operand_type: i32, operand: i32 },
+        316 => ChangeLevel { actor_id: i32, operation: i32, operand_type: i32, operand: i32 },
+        317 => ChangeParameters { actor_id: i32, parameter: i32, operation: i32, operand_type: i32, operand: i32 },
+        318 => ChangeSkills { actor_id: i32, operation: i32, skill_id: i32 },
+        319 => ChangeEquipment { actor_id: i32, kind: i32, equipment_id: i32 },
+        320 => ChangeActorName { actor_id: i32, name: String },
+        321 => ChangeActorClass { actor_id: i32, class_id: i32 },
+        322 => ChangeActorGraphic { actor_id: i32, character_name: String, character_hue: i32, battler_name: String, battler_hue: i32 },
+        331 => ChangeEnemyHp { enemy_index: i32, operation: i32, operand_type: i32, operand: i32, allow_knockout: bool },
+        332 => ChangeEnemySp { enemy_index: i32, operation: i32, operand_type: i32, operand: i32 },
+        333 => ChangeEnemyState { enemy_index: i32, operation: i32, state_id: i32 },
+        334 => EnemyRecoverAll { enemy_index: i32 },
+        335 => EnemyAppearance { enemy_index: i32 },
+        336 => EnemyTransform { enemy_index: i32, enemy_id: i32 },
+        337 => ShowBattleAnimation { target_type: i32, target_index: i32, animation_id: i32 },
+        338 => DealDamage { target_type: i32, target_index: i32, operand_type: i32, operand: i32 },
+        339 => ForceAction { battler_type: i32, battler_index: i32, kind: i32, basic: i32, target: i32, forcing: i32 },
+        340 => AbortBattle {},
+        351 => CallMenuScreen {},
+        352 => CallSaveScreen {},
+        353 => GameOver {},
+        354 => ReturnToTitleScreen {},
+        355 => Script { text: String },
+        655 => ScriptContinued { text: String },
+    }
+}
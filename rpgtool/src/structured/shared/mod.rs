@@ -4,6 +4,7 @@ mod mapinfo;
 mod move_route;
 mod parameter_type;
 mod script;
+mod typed;
 
 pub use audio_file::*;
 pub use event::*;
@@ -11,6 +12,7 @@ pub use mapinfo::*;
 pub use move_route::*;
 pub use parameter_type::*;
 pub use script::Script;
+pub use typed::*;
 
 use crate::structured::NilPadded;
 
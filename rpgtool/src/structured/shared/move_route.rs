@@ -1,5 +1,5 @@
 
-use super::ParameterType;
+use crate::structured::{AudioFile, ParameterType, typed_commands, typed_move_commands};
 
 #[derive(Default, Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
 #[derive(alox_48::Deserialize, alox_48::Serialize)]
@@ -10,7 +10,8 @@ pub struct MoveRoute {
     pub list: Vec<MoveCommand>,
 }
 
-#[derive(Default, Debug, serde::Deserialize, serde::Serialize, Clone, PartialEq)]
+/// Serialized typed or raw depending on [`set_typed_move_commands`](crate::structured::set_typed_move_commands).
+#[derive(Default, Debug, Clone, PartialEq)]
 #[derive(alox_48::Deserialize, alox_48::Serialize)]
 #[marshal(class = "RPG::MoveCommand")]
 #[allow(missing_docs)]
@@ -18,3 +19,131 @@ pub struct MoveCommand {
     pub code: u16,
     pub parameters: Vec<ParameterType>,
 }
+
+impl serde::Serialize for MoveCommand {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        if typed_move_commands() {
+            let mut s = serializer.serialize_struct("MoveCommand", 1)?;
+            let command = TypedMoveCommand::from_raw(self.code, &self.parameters);
+            s.serialize_field("command", &command)?;
+            s.end()
+        } else {
+            let mut s = serializer.serialize_struct("MoveCommand", 2)?;
+            s.serialize_field("code", &self.code)?;
+            s.serialize_field("parameters", &self.parameters)?;
+            s.end()
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for MoveCommand {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = MoveCommand;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                formatter.write_str("a raw or typed move command")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::MapAccess<'de>,
+            {
+                use serde::de::Error;
+
+                let mut code = None;
+                let mut parameters = None;
+                let mut command = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "code" => code = Some(map.next_value()?),
+                        "parameters" => parameters = Some(map.next_value()?),
+                        "command" => command = Some(map.next_value::<TypedMoveCommand>()?),
+                        _ => {
+                            map.next_value::<serde::de::IgnoredAny>()?;
+                        }
+                    }
+                }
+
+                let (code, parameters) = match (command, code, parameters) {
+                    (Some(command), None, None) => command.to_raw(),
+                    (None, Some(code), Some(parameters)) => (code, parameters),
+                    (Some(_), ..) => {
+                        return Err(A::Error::custom(
+                            "a typed command can't also have a code or parameters",
+                        ));
+                    }
+                    (None, None, _) => return Err(A::Error::missing_field("code")),
+                    (None, _, None) => return Err(A::Error::missing_field("parameters")),
+                };
+
+                Ok(MoveCommand { code, parameters })
+            }
+        }
+
+        const FIELDS: &[&str] = &["code", "parameters", "command"];
+        deserializer.deserialize_struct("MoveCommand", FIELDS, Visitor)
+    }
+}
+
+typed_commands! {
+    /// RGSS1 move commands.
+    pub enum TypedMoveCommand {
+        0 => End {},
+        1 => MoveDown {},
+        2 => MoveLeft {},
+        3 => MoveRight {},
+        4 => MoveUp {},
+        5 => MoveLowerLeft {},
+        6 => MoveLowerRight {},
+        7 => MoveUpperLeft {},
+        8 => MoveUpperRight {},
+        9 => MoveAtRandom {},
+        10 => MoveTowardPlayer {},
+        11 => MoveAwayFromPlayer {},
+        12 => StepForward {},
+        13 => StepBackward {},
+        14 => Jump { x: i32, y: i32 },
+        15 => Wait { frames: i32 },
+        16 => TurnDown {},
+        17 => TurnLeft {},
+        18 => TurnRight {},
+        19 => TurnUp {},
+        20 => TurnRight90 {},
+        21 => TurnLeft90 {},
+        22 => Turn180 {},
+        23 => TurnRightOrLeft90 {},
+        24 => TurnAtRandom {},
+        25 => TurnTowardPlayer {},
+        26 => TurnAwayFromPlayer {},
+        27 => SwitchOn { switch_id: i32 },
+        28 => SwitchOff { switch_id: i32 },
+        29 => ChangeSpeed { speed: i32 },
+        30 => ChangeFrequency { frequency: i32 },
+        31 => MoveAnimationOn {},
+        32 => MoveAnimationOff {},
+        33 => StopAnimationOn {},
+        34 => StopAnimationOff {},
+        35 => DirectionFixOn {},
+        36 => DirectionFixOff {},
+        37 => ThroughOn {},
+        38 => ThroughOff {},
+        39 => AlwaysOnTopOn {},
+        40 => AlwaysOnTopOff {},
+        41 => ChangeGraphic { character_name: String, character_hue: i32, direction: i32, pattern: i32 },
+        42 => ChangeOpacity { opacity: i32 },
+        43 => ChangeBlending { blend_type: i32 },
+        44 => PlaySe { se: AudioFile },
+        45 => Script { text: String },
+    }
+}
@@ -1,6 +1,19 @@
 use alox_48::Value;
 
-use crate::structured::{AudioFile, Color, MoveCommand, MoveRoute, Tone};
+use crate::structured::{AudioFile, Bgm, Bgs, Color, Me, MoveCommand, MoveRoute, Se, Tone};
+
+// FIXME like the typed command switches, there's no way to pass this down to a Serialize impl
+thread_local! {
+    static ENCODED_STRINGS: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Whether string parameters are written with an encoding, like Ruby 1.9 (VX Ace) does.
+/// Ruby 1.8 (XP and VX) strings don't have one.
+///
+/// This is per thread, so it must be set on the thread doing the conversion.
+pub fn set_encoded_string_parameters(encoded: bool) {
+    ENCODED_STRINGS.set(encoded);
+}
 
 #[derive(Debug, Clone, PartialEq)]
 #[derive(serde::Deserialize, serde::Serialize)]
@@ -13,6 +26,10 @@ pub enum ParameterType {
     Color(Color),
     Tone(Tone),
     AudioFile(AudioFile),
+    Bgm(Bgm),
+    Bgs(Bgs),
+    Me(Me),
+    Se(Se),
     Float(f64),
     MoveRoute(MoveRoute),
     MoveCommand(MoveCommand),
@@ -42,8 +59,8 @@ impl TryFrom<alox_48::Value> for ParameterType {
             }
             Value::Bool(v) => Self::Bool(v),
             Value::Userdata(userdata) => match userdata.class.as_str() {
-                "Color" => Self::Color(Color::from(userdata)),
-                "Tone" => Self::Tone(Tone::from(userdata)),
+                "Color" => Self::Color(Color::try_from(userdata)?),
+                "Tone" => Self::Tone(Tone::try_from(userdata)?),
                 _ => {
                     return Err(alox_48::DeError::custom(format!(
                         "unknown userdata parameter {userdata:#?}"
@@ -52,6 +69,10 @@ impl TryFrom<alox_48::Value> for ParameterType {
             },
             Value::Object(alox_48::Object { ref class, .. }) => match class.as_str() {
                 "RPG::AudioFile" => Self::AudioFile(alox_48::from_value(&value)?),
+                "RPG::BGM" => Self::Bgm(alox_48::from_value(&value)?),
+                "RPG::BGS" => Self::Bgs(alox_48::from_value(&value)?),
+                "RPG::ME" => Self::Me(alox_48::from_value(&value)?),
+                "RPG::SE" => Self::Se(alox_48::from_value(&value)?),
                 "RPG::MoveRoute" => Self::MoveRoute(alox_48::from_value(&value)?),
                 "RPG::MoveCommand" => Self::MoveCommand(alox_48::from_value(&value)?),
                 _ => {
@@ -80,6 +101,7 @@ impl TryFrom<ParameterType> for alox_48::Value {
             ParameterType::None => Value::Nil,
             ParameterType::Integer(v) => Value::Integer(v),
             ParameterType::Float(v) => Value::Float(v),
+            ParameterType::String(v) if ENCODED_STRINGS.get() => alox_48::to_value(v)?,
             ParameterType::String(v) => Value::String(v.into()),
             ParameterType::Array(v) => {
                 let array = v
@@ -92,9 +114,45 @@ impl TryFrom<ParameterType> for alox_48::Value {
             ParameterType::Color(v) => Value::Userdata(v.into()),
             ParameterType::Tone(v) => Value::Userdata(v.into()),
             ParameterType::AudioFile(v) => alox_48::to_value(v)?,
+            ParameterType::Bgm(v) => alox_48::to_value(v)?,
+            ParameterType::Bgs(v) => alox_48::to_value(v)?,
+            ParameterType::Me(v) => alox_48::to_value(v)?,
+            ParameterType::Se(v) => alox_48::to_value(v)?,
             ParameterType::MoveRoute(v) => alox_48::to_value(v)?,
             ParameterType::MoveCommand(v) => alox_48::to_value(v)?,
         };
         Ok(value)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::structured::EventCommand;
+
+    /// `RPG::EventCommand.new(401, 0, [text])`, where `text` is already marshaled.
+    fn show_text(text: &[u8]) -> Vec<u8> {
+        let mut data = b"\x04\x08o:\x16RPG::EventCommand\x08".to_vec();
+        data.extend_from_slice(b":\x0a@codei\x02\x91\x01:\x0c@indenti\x00:\x10@parameters[\x06");
+        data.extend_from_slice(text);
+        data
+    }
+
+    fn assert_round_trip(data: &[u8]) {
+        let command: EventCommand = alox_48::from_bytes(data).expect("should deserialize");
+        let output = alox_48::to_bytes(&command).expect("should serialize");
+        assert_eq!(output, data);
+    }
+
+    #[test]
+    fn plain_strings() {
+        set_encoded_string_parameters(false);
+        assert_round_trip(&show_text(b"\"\x0aHello"));
+    }
+
+    #[test]
+    fn encoded_strings() {
+        set_encoded_string_parameters(true);
+        assert_round_trip(&show_text(b"I\"\x0aHello\x06:\x06ET"));
+    }
+}
@@ -0,0 +1,157 @@
+//! Typed representations of event and move commands.
+//!
+//! RGSS stores commands as a code and an array of parameters, which is hard to read in text formats.
+//! These are an optional alternative that give each command a name and its parameters names.
+//!
+//! A command is only shown typed if it converts back to the exact same parameters,
+//! otherwise it falls back to the raw form so nothing is lost.
+
+use crate::structured::{AudioFile, Color, MoveCommand, MoveRoute, ParameterType, Tone};
+
+// FIXME like BYTES_ALLOWED in common, serde gives us no way to pass options down to a Serialize impl
+thread_local! {
+    static TYPED_EVENT_COMMANDS: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+    static TYPED_MOVE_COMMANDS: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Whether event commands are serialized typed. Deserializing always accepts both.
+///
+/// This is per thread, so it must be set on the thread doing the conversion.
+pub fn set_typed_event_commands(typed: bool) {
+    TYPED_EVENT_COMMANDS.set(typed);
+}
+
+pub(crate) fn typed_event_commands() -> bool {
+    TYPED_EVENT_COMMANDS.get()
+}
+
+/// Like [`set_typed_event_commands`], but for move commands.
+pub fn set_typed_move_commands(typed: bool) {
+    TYPED_MOVE_COMMANDS.set(typed);
+}
+
+pub(crate) fn typed_move_commands() -> bool {
+    TYPED_MOVE_COMMANDS.get()
+}
+
+/// A value that can be stored as a command parameter.
+pub trait Param: Sized {
+    /// Returns `None` if the parameter isn't of this type.
+    fn take(param: &ParameterType) -> Option<Self>;
+
+    fn put(&self) -> ParameterType;
+}
+
+macro_rules! param {
+    ($ty:ty, $variant:ident) => {
+        impl Param for $ty {
+            fn take(param: &ParameterType) -> Option<Self> {
+                match param {
+                    ParameterType::$variant(v) => Some(v.clone()),
+                    _ => None,
+                }
+            }
+
+            fn put(&self) -> ParameterType {
+                ParameterType::$variant(self.clone())
+            }
+        }
+    };
+}
+
+param!(i32, Integer);
+param!(String, String);
+param!(bool, Bool);
+param!(Color, Color);
+param!(Tone, Tone);
+param!(AudioFile, AudioFile);
+param!(MoveRoute, MoveRoute);
+param!(MoveCommand, MoveCommand);
+
+impl Param for Vec<String> {
+    fn take(param: &ParameterType) -> Option<Self> {
+        match param {
+            ParameterType::Array(array) => array.iter().map(String::take).collect(),
+            _ => None,
+        }
+    }
+
+    fn put(&self) -> ParameterType {
+        ParameterType::Array(self.iter().map(Param::put).collect())
+    }
+}
+
+/// Defines an enum of typed commands, with a `Raw` variant for anything unrecognized.
+///
+/// Fields are taken from the parameters in order. `..rest` collects any remaining parameters,
+/// for commands whose parameters depend on an earlier one.
+macro_rules! typed_commands {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $code:literal => $variant:ident { $($field:ident: $ty:ty),* $(, ..$rest:ident)? $(,)? }
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq)]
+        #[derive(serde::Deserialize, serde::Serialize)]
+        pub enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant { $($field: $ty,)* $($rest: Vec<ParameterType>,)? },
+            )*
+            /// A command that isn't known, or doesn't have the parameters it should.
+            Raw {
+                code: u16,
+                parameters: Vec<ParameterType>,
+            },
+        }
+
+        impl $name {
+            /// Converts a raw command, falling back to [`Self::Raw`] if it can't be typed losslessly.
+            pub fn from_raw(code: u16, parameters: &[ParameterType]) -> Self {
+                match Self::typed(code, parameters) {
+                    Some(typed) if typed.to_raw() == (code, parameters.to_vec()) => typed,
+                    _ => Self::Raw {
+                        code,
+                        parameters: parameters.to_vec(),
+                    },
+                }
+            }
+
+            #[allow(unused_mut, unused_variables)]
+            fn typed(code: u16, parameters: &[ParameterType]) -> Option<Self> {
+                let mut iter = parameters.iter();
+                let typed = match code {
+                    $(
+                        $code => {
+                            $(let $field = <$ty as $crate::structured::Param>::take(iter.next()?)?;)*
+                            $(let $rest = iter.cloned().collect();)?
+                            Self::$variant { $($field,)* $($rest)? }
+                        }
+                    )*
+                    _ => return None,
+                };
+                Some(typed)
+            }
+
+            pub fn to_raw(&self) -> (u16, Vec<ParameterType>) {
+                match self {
+                    $(
+                        Self::$variant { $($field,)* $($rest)? } => {
+                            #[allow(unused_mut)]
+                            let mut parameters = vec![$($crate::structured::Param::put($field)),*];
+                            $(parameters.extend($rest.iter().cloned());)?
+                            ($code, parameters)
+                        }
+                    )*
+                    Self::Raw { code, parameters } => (*code, parameters.clone()),
+                }
+            }
+        }
+    };
+}
+pub(crate) use typed_commands;
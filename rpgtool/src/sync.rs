@@ -0,0 +1,662 @@
+use clap::{CommandFactory, error::ErrorKind};
+use common::Format;
+use notify::Watcher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::structured;
+use crate::{Cli, GameVer, SyncArgs, manifest, walk};
+
+// How long to wait for more changes before converting, as editors often write a file in several steps.
+const SETTLE_TIME: Duration = Duration::from_millis(200);
+
+/// One of the two directories being synced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Side {
+    Data,
+    Text,
+}
+
+impl Side {
+    fn other(self) -> Side {
+        match self {
+            Side::Data => Side::Text,
+            Side::Text => Side::Data,
+        }
+    }
+}
+
+/// What syncing a pair did.
+enum Outcome {
+    InSync,
+    /// Converted from this side to the other.
+    Converted(Side),
+    /// Both sides changed since the last sync.
+    Conflict,
+}
+
+/// A data file and its text counterpart. Either may not exist yet.
+struct Pair {
+    /// The data file's path relative to the data directory.
+    key: String,
+    data: PathBuf,
+    text: PathBuf,
+}
+
+impl Pair {
+    fn path(&self, side: Side) -> &Path {
+        match side {
+            Side::Data => &self.data,
+            Side::Text => &self.text,
+        }
+    }
+}
+
+/// The hashes of both files as of the last sync.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct Record {
+    data: String,
+    text: String,
+}
+
+impl Record {
+    fn hash(&self, side: Side) -> &str {
+        match side {
+            Side::Data => &self.data,
+            Side::Text => &self.text,
+        }
+    }
+}
+
+#[derive(Default, serde::Serialize, serde::Deserialize)]
+struct SyncManifest {
+    /// The game version and text format. If these change, the records are useless.
+    settings: String,
+    files: BTreeMap<String, Record>,
+}
+
+/// The data directory and its text mirror.
+struct Sides {
+    data: PathBuf,
+    text: PathBuf,
+    data_ext: &'static str,
+    format: Format,
+    game_version: GameVer,
+}
+
+impl Sides {
+    fn settings(&self) -> String {
+        format!("{} {:?}", self.game_version.name(), self.format)
+    }
+
+    fn manifest_path(&self) -> PathBuf {
+        self.text.join(manifest::SYNC_MANIFEST_NAME)
+    }
+
+    fn load_manifest(&self) -> SyncManifest {
+        let manifest: SyncManifest = std::fs::read(self.manifest_path())
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        if manifest.settings == self.settings() {
+            manifest
+        } else {
+            SyncManifest {
+                settings: self.settings(),
+                files: BTreeMap::new(),
+            }
+        }
+    }
+
+    fn save_manifest(&self, manifest: &SyncManifest) -> Result<(), String> {
+        let path = self.manifest_path();
+        let data = serde_json::to_vec_pretty(manifest).map_err(|e| e.to_string())?;
+        std::fs::write(&path, data).map_err(|e| format!("couldn't write {}: {e}", path.display()))
+    }
+
+    /// The pair `path` belongs to, and which side of it `path` is.
+    fn pair(&self, path: &Path) -> Option<(Side, Pair)> {
+        if manifest::is_manifest(path) {
+            return None;
+        }
+        let text_ext = self.format.file_ext();
+
+        let (side, relative) = if let Ok(relative) = path.strip_prefix(&self.data)
+            && path.extension().is_some_and(|ext| ext == self.data_ext)
+        {
+            (Side::Data, relative.to_path_buf())
+        } else if let Ok(relative) = path.strip_prefix(&self.text)
+            && path.extension().is_some_and(|ext| ext == text_ext)
+        {
+            (Side::Text, relative.with_extension(self.data_ext))
+        } else {
+            return None;
+        };
+
+        let pair = Pair {
+            key: relative.to_string_lossy().replace('\\', "/"),
+            data: self.data.join(&relative),
+            text: self.text.join(&relative).with_extension(text_ext),
+        };
+        Some((side, pair))
+    }
+
+    /// Every pair with a file on either side.
+    fn pairs(&self) -> Result<Vec<Pair>, String> {
+        let filter = walk::Filter::new(&[], &[])?;
+        let mut pairs = BTreeMap::new();
+        for root in [&self.data, &self.text] {
+            for entry in walk::entries(root, true, &filter, true)? {
+                if let Some((_, pair)) = self.pair(&entry.path) {
+                    pairs.entry(pair.key.clone()).or_insert(pair);
+                }
+            }
+        }
+        Ok(pairs.into_values().collect())
+    }
+
+    /// Converts the `from` side of `pair` to the other side.
+    fn convert(&self, pair: &Pair, from: Side) -> Result<(), String> {
+        let (src, dest, formats) = match from {
+            Side::Data => (&pair.data, &pair.text, (Format::Marshal, self.format)),
+            Side::Text => (&pair.text, &pair.data, (self.format, Format::Marshal)),
+        };
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("failed to create {}: {e}", parent.display()))?;
+        }
+        structured::convert_file(self.game_version, src, dest, formats.0, formats.1)
+    }
+
+    /// Whether the text file is exactly what converting the data file would give.
+    fn in_sync(&self, pair: &Pair) -> Result<bool, String> {
+        let data = std::fs::read(&pair.data)
+            .map_err(|e| format!("couldn't open {}: {e}", pair.data.display()))?;
+        let filename = pair
+            .data
+            .file_prefix()
+            .and_then(|prefix| prefix.to_str())
+            .unwrap_or_default();
+        let text = structured::convert_bytes(
+            self.game_version,
+            filename,
+            &data,
+            Format::Marshal,
+            self.format,
+        )
+        .map_err(|e| format!("failed to parse {}: {e}", pair.data.display()))?;
+        Ok(std::fs::read(&pair.text).ok() == Some(text))
+    }
+}
+
+fn record(pair: &Pair) -> Result<Record, String> {
+    Ok(Record {
+        data: manifest::hash(&pair.data)?,
+        text: manifest::hash(&pair.text)?,
+    })
+}
+
+pub fn sync(args: SyncArgs) {
+    let SyncArgs {
+        data,
+        text,
+        game_version,
+        format,
+        watch,
+        prefer,
+    } = args;
+
+    if format == Format::Marshal {
+        let mut command = Cli::command();
+        command
+            .error(
+                ErrorKind::DisplayHelp,
+                "the text directory needs a text format",
+            )
+            .exit()
+    }
+
+    let game_version = if let Some(game_version) = game_version {
+        game_version
+    } else {
+        let detection = structured::detect::detect(&data, &text, Format::Marshal, None);
+        let Some(detection) = detection else {
+            let mut command = Cli::command();
+            command
+                .error(
+                    ErrorKind::DisplayHelp,
+                    "unable to determine game version, please specify it",
+                )
+                .exit()
+        };
+        eprintln!(
+            "[INFO]: Detected {} from {}",
+            detection.game_version.name(),
+            detection.reason
+        );
+        detection.game_version
+    };
+
+    if let Err(e) = std::fs::create_dir_all(&text) {
+        eprintln!("failed to create {}: {e}", text.display());
+        return;
+    }
+    // paths from the watcher are absolute, so ours need to be too
+    let (data, text) = match (std::fs::canonicalize(&data), std::fs::canonicalize(&text)) {
+        (Ok(data), Ok(text)) => (data, text),
+        (Err(e), _) => {
+            eprintln!("failed to read {}: {e}", data.display());
+            return;
+        }
+        (_, Err(e)) => {
+            eprintln!("failed to read {}: {e}", text.display());
+            return;
+        }
+    };
+
+    let data_ext = match game_version {
+        GameVer::RPGXP => "rxdata",
+        GameVer::RPGVX => "rvdata",
+        GameVer::RPGVXAce => "rvdata2",
+    };
+    let sides = Sides {
+        data,
+        text,
+        data_ext,
+        format,
+        game_version,
+    };
+
+    // a one-shot sync first, so we start off with both sides in sync
+    let totals = match sync_all(&sides, prefer) {
+        Ok(totals) => totals,
+        Err(e) => {
+            eprintln!("[FAIL]: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if watch {
+        if let Err(e) = watch_sides(&sides) {
+            eprintln!("failed to watch for changes: {e}");
+            std::process::exit(1);
+        }
+        return;
+    }
+    if totals.conflicts > 0 {
+        eprintln!(
+            "{} file(s) changed on both sides since the last sync, pass --prefer to pick a side",
+            totals.conflicts
+        );
+    }
+    if totals.failures > 0 {
+        eprintln!("{} file(s) failed to convert", totals.failures);
+    }
+    if totals.conflicts > 0 || totals.failures > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Pairs that a sync couldn't bring in sync.
+#[derive(Default)]
+struct Totals {
+    conflicts: usize,
+    failures: usize,
+}
+
+fn report_conflict(pair: &Pair) {
+    eprintln!(
+        "[CONFLICT]: {} and {} both changed since the last sync",
+        pair.data.display(),
+        pair.text.display()
+    );
+}
+
+/// Syncs every pair, returning how many had conflicts or failed to convert.
+fn sync_all(sides: &Sides, prefer: Option<Side>) -> Result<Totals, String> {
+    let mut manifest = sides.load_manifest();
+    let mut totals = Totals::default();
+
+    let pairs = sides.pairs()?;
+    // pairs deleted on both sides are gone for good
+    manifest
+        .files
+        .retain(|key, _| pairs.iter().any(|pair| &pair.key == key));
+
+    for pair in pairs {
+        let result = sync_pair(sides, &pair, manifest.files.get(&pair.key), prefer);
+        match result {
+            Ok(outcome @ (Outcome::InSync | Outcome::Converted(_))) => {
+                if let Outcome::Converted(from) = outcome {
+                    let (from, to) = (pair.path(from), pair.path(from.other()));
+                    eprintln!("[INFO]: {} -> {}", from.display(), to.display());
+                }
+                manifest.files.insert(pair.key.clone(), record(&pair)?);
+            }
+            Ok(Outcome::Conflict) => {
+                report_conflict(&pair);
+                totals.conflicts += 1;
+            }
+            Err(e) => {
+                eprintln!("[FAIL]: {e}");
+                totals.failures += 1;
+            }
+        }
+    }
+
+    sides.save_manifest(&manifest)?;
+    Ok(totals)
+}
+
+/// Brings one pair in sync, unless both sides changed and there's no preferred side.
+fn sync_pair(
+    sides: &Sides,
+    pair: &Pair,
+    record: Option<&Record>,
+    prefer: Option<Side>,
+) -> Result<Outcome, String> {
+    let changed = |side: Side| -> Result<bool, String> {
+        let hash = manifest::hash(pair.path(side))?;
+        Ok(record.is_none_or(|record| record.hash(side) != hash))
+    };
+
+    let from = match (pair.data.exists(), pair.text.exists()) {
+        // deletions aren't synced, the file is just brought back
+        (true, false) => Some(Side::Data),
+        (false, true) => Some(Side::Text),
+        (false, false) => None,
+        // without a record we can't tell what changed, but they may already match
+        (true, true) if record.is_none() && sides.in_sync(pair)? => None,
+        (true, true) => match (changed(Side::Data)?, changed(Side::Text)?) {
+            (false, false) => None,
+            (true, false) => Some(Side::Data),
+            (false, true) => Some(Side::Text),
+            (true, true) if prefer.is_some() => prefer,
+            (true, true) => return Ok(Outcome::Conflict),
+        },
+    };
+
+    let Some(from) = from else {
+        return Ok(Outcome::InSync);
+    };
+    sides.convert(pair, from)?;
+    Ok(Outcome::Converted(from))
+}
+
+/// Converts files as they change, until interrupted.
+fn watch_sides(sides: &Sides) -> notify::Result<()> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(&sides.data, notify::RecursiveMode::Recursive)?;
+    watcher.watch(&sides.text, notify::RecursiveMode::Recursive)?;
+
+    eprintln!(
+        "[INFO]: Watching {} and {}",
+        sides.data.display(),
+        sides.text.display()
+    );
+
+    // Converting a file changes its counterpart, which we'd otherwise see as a change and convert
+    // right back. The manifest records what both sides look like after converting, so it's skipped.
+    let mut manifest = sides.load_manifest();
+
+    while let Ok(event) = receiver.recv() {
+        let mut changed = BTreeSet::new();
+        let mut event = Some(event);
+        while let Some(next) = event {
+            let next = next?;
+            if next.kind.is_create() || next.kind.is_modify() {
+                changed.extend(next.paths);
+            }
+            event = receiver.recv_timeout(SETTLE_TIME).ok();
+        }
+
+        // both sides of a pair may have changed, which is one conflict, not two
+        let mut seen = BTreeSet::new();
+        for path in changed {
+            let Some((side, pair)) = sides.pair(&path) else {
+                continue;
+            };
+            if seen.contains(&pair.key) {
+                continue;
+            }
+            // it may have been a temporary file that's already gone
+            let Ok(hash) = manifest::hash(&path) else {
+                continue;
+            };
+            let last = manifest.files.get(&pair.key);
+            if last.is_some_and(|last| last.hash(side) == hash) {
+                continue;
+            }
+            seen.insert(pair.key.clone());
+
+            // converting over a counterpart that changed too would lose those changes
+            match counterpart_changed(sides, &pair, side, last) {
+                Ok(false) => {}
+                Ok(true) => {
+                    report_conflict(&pair);
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("[FAIL]: {e}");
+                    continue;
+                }
+            }
+
+            let result = sides.convert(&pair, side).and_then(|()| record(&pair));
+            match result {
+                Ok(record) => {
+                    let to = pair.path(side.other());
+                    eprintln!("[INFO]: {} -> {}", path.display(), to.display());
+                    manifest.files.insert(pair.key, record);
+                }
+                // likely a file that's only half written, there'll be another event once it's done
+                Err(e) => eprintln!("[FAIL]: {e}"),
+            }
+        }
+
+        if let Err(e) = sides.save_manifest(&manifest) {
+            eprintln!("[FAIL]: {e}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether the other side of `pair` changed since the last sync too, like a pair left in conflict.
+fn counterpart_changed(
+    sides: &Sides,
+    pair: &Pair,
+    side: Side,
+    last: Option<&Record>,
+) -> Result<bool, String> {
+    let other = pair.path(side.other());
+    if !other.exists() {
+        return Ok(false);
+    }
+    let changed = match last {
+        Some(last) => manifest::hash(other)? != last.hash(side.other()),
+        None => true,
+    };
+    // they may match anyways, if both sides were edited the same way
+    Ok(changed && !sides.in_sync(pair)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_sides(name: &str) -> Sides {
+        let root = std::env::temp_dir().join(format!("rpgtool-sync-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        for dir in ["Data", "Text"] {
+            std::fs::create_dir_all(root.join(dir)).expect("temp dir is writable");
+        }
+        Sides {
+            data: root.join("Data"),
+            text: root.join("Text"),
+            data_ext: "rxdata",
+            format: Format::Json,
+            game_version: GameVer::RPGXP,
+        }
+    }
+
+    /// A marshaled integer from 1 to 122.
+    fn marshal(n: u8) -> Vec<u8> {
+        vec![4, 8, b'i', n + 5]
+    }
+
+    fn json(n: u8) -> Vec<u8> {
+        structured::convert_bytes(
+            GameVer::RPGXP,
+            "Misc",
+            &marshal(n),
+            Format::Marshal,
+            Format::Json,
+        )
+        .expect("integer converts")
+    }
+
+    fn pair(sides: &Sides) -> Pair {
+        sides
+            .pair(&sides.data.join("Misc.rxdata"))
+            .expect("data file has a pair")
+            .1
+    }
+
+    fn write(path: &Path, data: &[u8]) {
+        std::fs::write(path, data).expect("temp dir is writable");
+    }
+
+    fn read(path: &Path) -> Vec<u8> {
+        std::fs::read(path).expect("file exists")
+    }
+
+    fn sync_clean(sides: &Sides, prefer: Option<Side>) -> Totals {
+        let totals = sync_all(sides, prefer).expect("sync runs");
+        assert_eq!(totals.failures, 0);
+        totals
+    }
+
+    #[test]
+    fn pairs() {
+        let sides = temp_sides("pairs");
+        let (side, pair) = sides
+            .pair(&sides.text.join("Maps/Map001.json"))
+            .expect("text file has a pair");
+        assert_eq!(side, Side::Text);
+        assert_eq!(pair.key, "Maps/Map001.rxdata");
+        assert_eq!(pair.data, sides.data.join("Maps/Map001.rxdata"));
+
+        assert!(sides.pair(&sides.data.join("Misc.json")).is_none());
+        assert!(sides.pair(&sides.manifest_path()).is_none());
+    }
+
+    #[test]
+    fn manifest() {
+        let sides = temp_sides("manifest");
+        let pair = pair(&sides);
+        write(&pair.data, &marshal(1));
+
+        assert_eq!(sync_clean(&sides, None).conflicts, 0);
+        assert_eq!(read(&pair.text), json(1));
+        let manifest = sides.load_manifest();
+        assert_eq!(manifest.settings, "RPG Maker XP Json");
+        let record = &manifest.files["Misc.rxdata"];
+        assert_eq!(record.data, manifest::hash(&pair.data).expect("hashes"));
+        assert_eq!(record.text, manifest::hash(&pair.text).expect("hashes"));
+
+        // other settings make the records useless
+        let other = Sides {
+            format: Format::Yaml,
+            ..temp_sides("manifest-other")
+        };
+        std::fs::copy(sides.manifest_path(), other.manifest_path()).expect("copies");
+        assert!(other.load_manifest().files.is_empty());
+
+        // records of pairs deleted on both sides are dropped
+        std::fs::remove_file(&pair.data).expect("exists");
+        std::fs::remove_file(&pair.text).expect("exists");
+        sync_clean(&sides, None);
+        assert!(sides.load_manifest().files.is_empty());
+    }
+
+    #[test]
+    fn one_side_changed() {
+        let sides = temp_sides("one-side");
+        let pair = pair(&sides);
+        write(&pair.data, &marshal(1));
+        sync_clean(&sides, None);
+
+        write(&pair.text, &json(2));
+        sync_clean(&sides, None);
+        assert_eq!(read(&pair.data), marshal(2));
+
+        // deletions aren't synced
+        std::fs::remove_file(&pair.text).expect("exists");
+        sync_clean(&sides, None);
+        assert_eq!(read(&pair.text), json(2));
+    }
+
+    #[test]
+    fn prefer() {
+        let sides = temp_sides("prefer");
+        let pair = pair(&sides);
+        write(&pair.data, &marshal(1));
+        sync_clean(&sides, None);
+
+        write(&pair.data, &marshal(2));
+        write(&pair.text, &json(3));
+        assert_eq!(sync_clean(&sides, None).conflicts, 1);
+        assert_eq!(read(&pair.data), marshal(2));
+        assert_eq!(read(&pair.text), json(3));
+
+        assert_eq!(sync_clean(&sides, Some(Side::Text)).conflicts, 0);
+        assert_eq!(read(&pair.data), marshal(3));
+
+        write(&pair.data, &marshal(4));
+        write(&pair.text, &json(5));
+        assert_eq!(sync_clean(&sides, Some(Side::Data)).conflicts, 0);
+        assert_eq!(read(&pair.text), json(4));
+    }
+
+    #[test]
+    fn without_a_record() {
+        let sides = temp_sides("no-record");
+        let pair = pair(&sides);
+        write(&pair.data, &marshal(1));
+        write(&pair.text, &json(1));
+        assert_eq!(sync_clean(&sides, None).conflicts, 0);
+
+        std::fs::remove_file(sides.manifest_path()).expect("exists");
+        write(&pair.text, &json(2));
+        assert_eq!(sync_clean(&sides, None).conflicts, 1);
+    }
+
+    #[test]
+    fn counterpart() {
+        let sides = temp_sides("counterpart");
+        let pair = pair(&sides);
+        write(&pair.data, &marshal(1));
+        sync_clean(&sides, None);
+        let manifest = sides.load_manifest();
+        let last = manifest.files.get("Misc.rxdata");
+
+        write(&pair.data, &marshal(2));
+        let changed = |last| counterpart_changed(&sides, &pair, Side::Data, last);
+        assert_eq!(changed(last), Ok(false));
+
+        write(&pair.text, &json(3));
+        assert_eq!(changed(last), Ok(true));
+        assert_eq!(changed(None), Ok(true));
+
+        // both sides edited the same way
+        write(&pair.text, &json(2));
+        assert_eq!(changed(last), Ok(false));
+        assert_eq!(changed(None), Ok(false));
+
+        std::fs::remove_file(&pair.text).expect("exists");
+        assert_eq!(changed(last), Ok(false));
+    }
+}
@@ -0,0 +1,81 @@
+use clap::{CommandFactory, error::ErrorKind};
+use common::Format;
+use std::io::Write;
+
+use crate::structured::{self, DataVisitor, GameData};
+use crate::{Cli, TextconvArgs};
+
+pub fn textconv(args: TextconvArgs) {
+    let TextconvArgs {
+        file,
+        game_version,
+        format,
+    } = args;
+
+    if format == Format::Marshal {
+        let mut command = Cli::command();
+        command
+            .error(ErrorKind::DisplayHelp, "textconv needs a text format")
+            .exit()
+    }
+
+    let game_version = game_version.or_else(|| {
+        file.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(structured::detect::from_ext)
+    });
+    let prefix = file
+        .file_prefix()
+        .and_then(|prefix| prefix.to_str())
+        .unwrap_or_default();
+
+    let visitor = WriteText {
+        path: &file,
+        format,
+    };
+    // git names the temporary files it passes us `XXXXXX_<name>`
+    let result = game_version
+        .and_then(|game_version| {
+            structured::dispatch(game_version, prefix, visitor).or_else(|| {
+                let (_, name) = prefix.split_once('_')?;
+                structured::dispatch(game_version, name, visitor)
+            })
+        })
+        .unwrap_or_else(|| write_text::<common::Value>(&file, format));
+
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
+
+#[derive(Clone, Copy)]
+struct WriteText<'a> {
+    path: &'a std::path::Path,
+    format: Format,
+}
+
+impl DataVisitor for WriteText<'_> {
+    type Output = Result<(), String>;
+
+    fn visit<T: GameData>(self) -> Self::Output {
+        write_text::<T>(self.path, self.format)
+    }
+}
+
+fn write_text<T: GameData>(path: &std::path::Path, format: Format) -> Result<(), String> {
+    let input =
+        std::fs::File::open(path).map_err(|e| format!("couldn't open {}: {e}", path.display()))?;
+    let input = std::io::BufReader::new(input);
+    let value: T = common::conv_read(Format::Marshal, input)
+        .map_err(|e| format!("failed to parse {}: {e}", path.display()))?;
+
+    let mut output = std::io::BufWriter::new(std::io::stdout().lock());
+    common::conv_write(value, format, &mut output)
+        .map_err(|e| format!("failed to convert {}: {e}", path.display()))?;
+    // JSON and RON don't end with a newline, which diff tools complain about
+    if format != Format::Yaml {
+        writeln!(output).map_err(|e| e.to_string())?;
+    }
+    output.flush().map_err(|e| e.to_string())
+}
@@ -0,0 +1,167 @@
+//! Game data as a generic tree of values, for comparing and merging files.
+//!
+//! Data is converted through its JSON representation, so anything `structured` can convert works here.
+
+use common::Format;
+
+use crate::structured::GameData;
+
+pub type Tree = serde_json::Value;
+
+/// Reads a data file. An empty file (like the base of a merge where both sides added the file)
+/// reads as `None`.
+pub fn read<T: GameData>(path: &std::path::Path, format: Format) -> Result<Option<T>, String> {
+    let data = std::fs::read(path).map_err(|e| format!("couldn't open {}: {e}", path.display()))?;
+    if data.is_empty() {
+        return Ok(None);
+    }
+    common::conv_read(format, data.as_slice())
+        .map(Some)
+        .map_err(|e| format!("failed to parse {}: {e}", path.display()))
+}
+
+pub fn to_tree<T: GameData>(value: T) -> Result<Tree, String> {
+    let mut json = vec![];
+    common::conv_write(value, Format::Json, &mut json).map_err(|e| e.to_string())?;
+    serde_json::from_slice(&json).map_err(|e| e.to_string())
+}
+
+pub fn from_tree<T: GameData>(tree: &Tree) -> Result<T, String> {
+    let json = serde_json::to_vec(tree).map_err(|e| e.to_string())?;
+    common::conv_read(Format::Json, json.as_slice()).map_err(|e| e.to_string())
+}
+
+/// One step from a value to a value inside it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    Field(String),
+    /// A position in an array.
+    Index(usize),
+    /// An entry in a database array, by its id.
+    Id(i64),
+    /// A cell of a table, by its coordinates.
+    Cell(Vec<usize>),
+}
+
+/// Where a value is in a file, like `Actors[3].name`.
+#[derive(Debug, Clone)]
+pub struct Path {
+    root: String,
+    segments: Vec<Segment>,
+}
+
+impl Path {
+    pub fn new(root: impl Into<String>) -> Self {
+        Self {
+            root: root.into(),
+            segments: vec![],
+        }
+    }
+
+    pub fn join(&self, segment: Segment) -> Self {
+        let mut path = self.clone();
+        path.segments.push(segment);
+        path
+    }
+}
+
+impl std::fmt::Display for Path {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.root)?;
+        for segment in &self.segments {
+            match segment {
+                // keys of id maps (like map events) are written like ids
+                Segment::Field(key) if key.parse::<i64>().is_ok() => write!(f, "[{key}]")?,
+                Segment::Field(field) => write!(f, ".{field}")?,
+                Segment::Index(index) => write!(f, "[{index}]")?,
+                Segment::Id(id) => write!(f, "[{id}]")?,
+                Segment::Cell(coords) => {
+                    let coords: Vec<_> = coords.iter().map(ToString::to_string).collect();
+                    write!(f, "[{}]", coords.join(", "))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Short, single line rendering of a value for messages.
+pub fn describe(value: Option<&Tree>) -> String {
+    const MAX_LEN: usize = 60;
+
+    let Some(value) = value else {
+        return "(nothing)".to_string();
+    };
+    let text = value.to_string();
+    if text.chars().count() > MAX_LEN {
+        let text: String = text.chars().take(MAX_LEN).collect();
+        format!("{text}...")
+    } else {
+        text
+    }
+}
+
+/// The keys of an object, with ids (like map event ids) in numeric order.
+pub fn sorted_keys<'a>(keys: impl IntoIterator<Item = &'a String>) -> Vec<&'a String> {
+    let mut keys: Vec<_> = keys.into_iter().collect();
+    keys.sort_by_key(|key| (key.parse::<i64>().ok(), *key));
+    keys.dedup();
+    keys
+}
+
+/// The id of an entry in a database array.
+pub fn id(value: &Tree) -> Option<i64> {
+    value.get("id").and_then(Tree::as_i64)
+}
+
+/// If every element of `array` is a database entry, returns them keyed by id.
+///
+/// Database arrays start with `nil`, which is skipped. The game looks entries up by index,
+/// so every id must be its entry's index in the array.
+pub fn entries(array: &[Tree]) -> Option<std::collections::BTreeMap<i64, &Tree>> {
+    let start = usize::from(array.first().is_some_and(Tree::is_null));
+
+    let mut entries = std::collections::BTreeMap::new();
+    for (index, value) in array.iter().enumerate().skip(start) {
+        let id = id(value)?;
+        if usize::try_from(id).ok() != Some(index) {
+            return None;
+        }
+        entries.insert(id, value);
+    }
+    Some(entries)
+}
+
+/// Whether `value` is a list of event commands, raw or typed.
+pub fn is_command_list(value: &Tree) -> bool {
+    value.as_array().is_some_and(|commands| {
+        !commands.is_empty()
+            && commands
+                .iter()
+                .all(|command| command.get("indent").is_some_and(Tree::is_u64))
+    })
+}
+
+/// The size of each dimension, if `value` is a table.
+pub fn table_dims(value: &Tree) -> Option<Vec<usize>> {
+    let object = value.as_object()?;
+    let dims: Vec<_> = ["xsize", "ysize", "zsize"]
+        .iter()
+        .map_while(|key| object.get(*key).and_then(Tree::as_u64))
+        .map_while(|size| usize::try_from(size).ok())
+        .collect();
+    // the only other field a table has is its data
+    (!dims.is_empty() && object.len() == dims.len() + 1 && object.contains_key("data"))
+        .then_some(dims)
+}
+
+/// The coordinates of the `index`th cell of a table.
+pub fn cell(dims: &[usize], mut index: usize) -> Vec<usize> {
+    dims.iter()
+        .map(|&size| {
+            let coord = index % size.max(1);
+            index /= size.max(1);
+            coord
+        })
+        .collect()
+}
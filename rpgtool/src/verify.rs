@@ -0,0 +1,270 @@
+use clap::{CommandFactory, error::ErrorKind};
+use common::Format;
+use indicatif::ProgressStyle;
+use rayon::prelude::*;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::structured::{self, DataVisitor, GameData};
+use crate::{Cli, VerifyArgs, walk};
+
+// How many bytes to show on either side of the first difference.
+const CONTEXT: usize = 8;
+
+#[allow(clippy::too_many_lines)]
+pub fn verify(args: VerifyArgs) {
+    let VerifyArgs {
+        src,
+        game_version,
+        format,
+        links,
+        fail_on_error,
+        recursive,
+        include,
+        exclude,
+        single_thread,
+        thread_count,
+    } = args;
+
+    if let Some(count) = thread_count {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(count)
+            .build_global()
+            .expect("failed to build thread pool");
+    }
+
+    let game_version = if let Some(game_version) = game_version {
+        game_version
+    } else {
+        let Some(detection) = structured::detect::detect(&src, &src, Format::Marshal, None) else {
+            let mut command = Cli::command();
+            command
+                .error(
+                    ErrorKind::DisplayHelp,
+                    "unable to determine game version, please specify it",
+                )
+                .exit()
+        };
+        eprintln!(
+            "[INFO]: Detected {} from {}",
+            detection.game_version.name(),
+            detection.reason
+        );
+        detection.game_version
+    };
+
+    let filter = match walk::Filter::new(&include, &exclude) {
+        Ok(filter) => filter,
+        Err(e) => {
+            eprintln!("invalid glob: {e}");
+            std::process::exit(1);
+        }
+    };
+    let entries = match walk::entries(&src, recursive, &filter, fail_on_error) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    let pb = indicatif::ProgressBar::new(entries.len() as _);
+    pb.set_style(
+        ProgressStyle::with_template(
+            "{spinner:.green} [{elapsed_precise}] [{bar:.cyan/blue}] {pos}/{len} verified",
+        )
+        .expect("should be valid")
+        .progress_chars("#>-"),
+    );
+    pb.enable_steady_tick(std::time::Duration::from_millis(50));
+
+    let failed = AtomicUsize::new(0);
+
+    let entry_fn = |entry: &walk::Entry| {
+        pb.inc(1);
+        let src_path = &entry.path;
+        // only marshal files can be round tripped
+        if !entry.is_file || Format::guess(src_path) != Some(Format::Marshal) {
+            pb.println(format!("[WARN]: Ignoring {}", src_path.display()));
+            return Some(());
+        }
+
+        let original = match std::fs::read(src_path) {
+            Ok(data) => data,
+            Err(e) => {
+                pb.suspend(|| eprintln!("[FAIL]: couldn't open {}: {e}", src_path.display()));
+                failed.fetch_add(1, Ordering::Relaxed);
+                return if fail_on_error { None } else { Some(()) };
+            }
+        };
+
+        let loose = if links {
+            round_trip_linked(
+                &original,
+                format,
+                common::links::FloatStyle::guess(src_path),
+            )
+        } else {
+            round_trip::<common::Value>(&original, format)
+        };
+        let mut results = vec![("loose", loose)];
+
+        let prefix = src_path.file_prefix().expect("there should be a prefix");
+        let visitor = RoundTrip {
+            original: &original,
+            format,
+        };
+        if let Some(structured) = prefix
+            .to_str()
+            .and_then(|filename| structured::dispatch(game_version, filename, visitor))
+        {
+            results.push(("structured", structured));
+        }
+
+        let mut ok = true;
+        for (kind, result) in results {
+            let message = match result {
+                Ok(output) => describe_difference(&original, &output),
+                Err(e) => Some(e),
+            };
+            if let Some(message) = message {
+                // printed directly, as the progress bar hides its messages when not in a terminal
+                pb.suspend(|| eprintln!("[FAIL]: {} ({kind}): {message}", src_path.display()));
+                ok = false;
+            }
+        }
+
+        if !ok {
+            failed.fetch_add(1, Ordering::Relaxed);
+            if fail_on_error {
+                pb.abandon();
+                return None;
+            }
+        }
+
+        Some(())
+    };
+
+    let result = if single_thread {
+        entries.iter().try_for_each(entry_fn)
+    } else {
+        entries.par_iter().try_for_each(entry_fn)
+    };
+
+    if result.is_some() {
+        pb.finish();
+    }
+
+    let failed = failed.into_inner();
+    if failed > 0 {
+        eprintln!("{failed} file(s) did not survive a round trip through {format:?}");
+        std::process::exit(1);
+    }
+}
+
+struct RoundTrip<'a> {
+    original: &'a [u8],
+    format: Format,
+}
+
+impl DataVisitor for RoundTrip<'_> {
+    type Output = Result<Vec<u8>, String>;
+
+    fn visit<T: GameData>(self) -> Self::Output {
+        round_trip::<T>(self.original, self.format)
+    }
+}
+
+/// Converts marshal data to `format` and back again.
+fn round_trip<T: GameData>(original: &[u8], format: Format) -> Result<Vec<u8>, String> {
+    let value: T = common::conv_read(Format::Marshal, original)
+        .map_err(|e| format!("failed to parse: {e}"))?;
+
+    let mut text = vec![];
+    common::conv_write(value, format, &mut text)
+        .map_err(|e| format!("failed to convert to {format:?}: {e}"))?;
+
+    let value: T = common::conv_read(format, text.as_slice())
+        .map_err(|e| format!("failed to parse {format:?}: {e}"))?;
+
+    let mut output = vec![];
+    common::conv_write(value, Format::Marshal, &mut output)
+        .map_err(|e| format!("failed to convert back to marshal: {e}"))?;
+
+    Ok(output)
+}
+
+/// [`round_trip`], but preserving object links.
+fn round_trip_linked(
+    original: &[u8],
+    format: Format,
+    floats: common::links::FloatStyle,
+) -> Result<Vec<u8>, String> {
+    let value = common::conv_read_linked(Format::Marshal, original)
+        .map_err(|e| format!("failed to parse: {e}"))?;
+
+    let mut text = vec![];
+    common::conv_write_linked(&value, format, floats, &mut text)
+        .map_err(|e| format!("failed to convert to {format:?}: {e}"))?;
+
+    let value = common::conv_read_linked(format, text.as_slice())
+        .map_err(|e| format!("failed to parse {format:?}: {e}"))?;
+
+    let mut output = vec![];
+    common::conv_write_linked(&value, Format::Marshal, floats, &mut output)
+        .map_err(|e| format!("failed to convert back to marshal: {e}"))?;
+
+    Ok(output)
+}
+
+/// Describes where `output` first differs from `original`, if it does, with the path of the value
+/// that differs.
+fn describe_difference(original: &[u8], output: &[u8]) -> Option<String> {
+    let offset = original
+        .iter()
+        .zip(output)
+        .position(|(a, b)| a != b)
+        .or_else(|| (original.len() != output.len()).then(|| original.len().min(output.len())))?;
+
+    // which value lost something is much more useful than the bytes alone
+    let location = match common::marshal_path_at(original, offset) {
+        Some(path) if !path.is_empty() => format!(" in {path}"),
+        Some(_) => String::new(),
+        None => " past the end of the original".to_owned(),
+    };
+
+    Some(format!(
+        "first difference at offset {offset:#x}{location} (original is {} bytes, round trip is {} bytes)\n  original:   {}\n  round trip: {}",
+        original.len(),
+        output.len(),
+        context(original, offset),
+        context(output, offset),
+    ))
+}
+
+/// Hex and ASCII dump of the bytes around `offset`, with the byte at `offset` bracketed.
+fn context(data: &[u8], offset: usize) -> String {
+    let start = offset.saturating_sub(CONTEXT);
+    let end = (offset + CONTEXT + 1).min(data.len());
+    let bytes = data.get(start..end).unwrap_or_default();
+
+    let mut hex = String::new();
+    let mut ascii = String::new();
+    for (i, &byte) in bytes.iter().enumerate() {
+        if start + i == offset {
+            let _ = write!(hex, "[{byte:02x}]");
+        } else {
+            let _ = write!(hex, " {byte:02x} ");
+        }
+        ascii.push(if byte.is_ascii_graphic() || byte == b' ' {
+            char::from(byte)
+        } else {
+            '.'
+        });
+    }
+    if offset >= data.len() {
+        hex.push_str("[EOF]");
+    }
+
+    format!("{start:#08x} {hex} |{ascii}|")
+}
@@ -0,0 +1,101 @@
+//! Finding the files a command should work on.
+
+use std::path::{Path, PathBuf};
+
+/// Something in the source directory.
+pub struct Entry {
+    pub path: PathBuf,
+    /// The path relative to the source directory, which is mirrored in the destination.
+    pub relative: PathBuf,
+    pub is_file: bool,
+}
+
+/// Glob filters on paths relative to the source directory.
+pub struct Filter {
+    include: Option<globset::GlobSet>,
+    exclude: Option<globset::GlobSet>,
+}
+
+impl Filter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, String> {
+        let build = |patterns: &[String]| {
+            if patterns.is_empty() {
+                return Ok(None);
+            }
+            let mut builder = globset::GlobSetBuilder::new();
+            for pattern in patterns {
+                let glob = globset::Glob::new(pattern).map_err(|e| e.to_string())?;
+                builder.add(glob);
+            }
+            builder.build().map(Some).map_err(|e| e.to_string())
+        };
+        Ok(Self {
+            include: build(include)?,
+            exclude: build(exclude)?,
+        })
+    }
+
+    pub fn matches(&self, relative: &Path) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|set| set.is_match(relative))
+            && self
+                .exclude
+                .as_ref()
+                .is_none_or(|set| !set.is_match(relative))
+    }
+}
+
+/// Lists the entries of `src` that pass `filter`, sorted by path.
+///
+/// Only the top level is listed unless `recursive` is set, in which case all files in
+/// subdirectories are listed instead of the subdirectories themselves.
+/// Entries that can't be read are skipped, unless `fail_on_error` is set.
+pub fn entries(
+    src: &Path,
+    recursive: bool,
+    filter: &Filter,
+    fail_on_error: bool,
+) -> Result<Vec<Entry>, String> {
+    let mut entries = vec![];
+
+    let max_depth = if recursive { usize::MAX } else { 1 };
+    let walk = walkdir::WalkDir::new(src).min_depth(1).max_depth(max_depth);
+    for entry in walk {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(e) if e.depth() == 0 => {
+                return Err(format!("failed to read {}: {e}", src.display()));
+            }
+            Err(e) if fail_on_error => return Err(format!("failed to read directory entry: {e}")),
+            Err(_) => continue,
+        };
+
+        if crate::manifest::is_manifest(entry.path()) {
+            continue;
+        }
+
+        let is_file = entry.file_type().is_file();
+        if recursive && !is_file {
+            continue;
+        }
+
+        let relative = entry
+            .path()
+            .strip_prefix(src)
+            .expect("entries should be in src")
+            .to_path_buf();
+        if !filter.matches(&relative) {
+            continue;
+        }
+
+        entries.push(Entry {
+            path: entry.into_path(),
+            relative,
+            is_file,
+        });
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(entries)
+}